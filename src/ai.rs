@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::GameState;
+use crate::collisions::*;
+use crate::constants::*;
+use crate::ennemies::Ennemy;
+use crate::entitypattern::EntityBehavior;
+use crate::player::Player;
+use crate::rng::GameRng;
+use crate::structures::Sanctuary;
+use crate::tick::TickSet;
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, think.in_set(TickSet::Ai).run_if(in_state(GameState::Playing)));
+    }
+}
+
+pub struct AiContext<'a> {
+    pub player: &'a Player,
+    pub player_distance: f32,
+    pub allies_chasing: usize,
+    pub shrines: &'a [(f32, f32)],
+}
+
+impl<'a> AiContext<'a> {
+    pub fn sees_player(&self) -> bool {
+        self.player.is_aggroable() && self.player_distance < ENNEMY_AGGRO_DISTANCE
+    }
+}
+
+#[derive(Default)]
+pub struct AiMemory {
+    pub last_seen_player: Option<(f32, f32)>,
+    pub guard_post: Option<(f32, f32)>,
+}
+
+pub trait AiNode: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn score(&self, ennemy: &Ennemy, memory: &AiMemory, context: &AiContext) -> f32;
+    fn run(&self, ennemy: &mut Ennemy, memory: &mut AiMemory, context: &AiContext, rng: &mut StdRng, collision_query: &Query<&CollisionComponent, Without<Ennemy>>);
+}
+
+#[derive(Component)]
+pub struct Brain {
+    nodes: Vec<Box<dyn AiNode>>,
+    memory: AiMemory,
+    current: Option<&'static str>,
+}
+
+impl Brain {
+    pub fn new() -> Self {
+        Brain { nodes: Vec::new(), memory: AiMemory::default(), current: None }
+    }
+
+    pub fn with(mut self, node: impl AiNode + 'static) -> Self {
+        self.nodes.push(Box::new(node));
+        self
+    }
+
+    pub fn skeleton() -> Self {
+        Brain::new()
+            .with(Patrol)
+            .with(Investigate)
+            .with(Chase)
+            .with(Flank)
+            .with(Retreat)
+    }
+
+    pub fn shrine_guard() -> Self {
+        Brain::skeleton().with(GuardShrine)
+    }
+
+    pub fn is_shrine_guard(&self) -> bool {
+        self.nodes.iter().any(|node| node.name() == GuardShrine.name())
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn current(&self) -> Option<&'static str> {
+        self.current
+    }
+
+    fn decide(&self, ennemy: &Ennemy, context: &AiContext) -> Option<usize> {
+        self.nodes.iter()
+            .enumerate()
+            .map(|(i, node)| {
+                let bonus = if self.current == Some(node.name()) { AI_CURRENT_NODE_BONUS } else { 0. };
+                (i, node.score(ennemy, &self.memory, context) + bonus)
+            })
+            .filter(|(_, score)| *score > 0.)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+    }
+}
+
+impl Default for Brain {
+    fn default() -> Self {
+        Brain::new()
+    }
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+pub struct Patrol;
+
+impl AiNode for Patrol {
+    fn name(&self) -> &'static str {
+        "patrol"
+    }
+
+    fn score(&self, _ennemy: &Ennemy, _memory: &AiMemory, _context: &AiContext) -> f32 {
+        0.1
+    }
+
+    fn run(&self, ennemy: &mut Ennemy, _memory: &mut AiMemory, _context: &AiContext, rng: &mut StdRng, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
+        ennemy.roaming(rng, collision_query);
+    }
+}
+
+pub struct Investigate;
+
+impl AiNode for Investigate {
+    fn name(&self) -> &'static str {
+        "investigate"
+    }
+
+    fn score(&self, _ennemy: &Ennemy, memory: &AiMemory, context: &AiContext) -> f32 {
+        if memory.last_seen_player.is_some() && !context.sees_player() { 0.4 } else { 0. }
+    }
+
+    fn run(&self, ennemy: &mut Ennemy, memory: &mut AiMemory, _context: &AiContext, _rng: &mut StdRng, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
+        if let Some(target) = memory.last_seen_player {
+            if distance(ennemy.get_pos(), target) < AI_INVESTIGATE_REACHED_DISTANCE {
+                memory.last_seen_player = None;
+            } else {
+                ennemy.go_to(target.0, target.1, false, collision_query);
+            }
+        }
+    }
+}
+
+pub struct Chase;
+
+impl AiNode for Chase {
+    fn name(&self) -> &'static str {
+        "chase"
+    }
+
+    fn score(&self, _ennemy: &Ennemy, _memory: &AiMemory, context: &AiContext) -> f32 {
+        if context.sees_player() { 0.7 } else { 0. }
+    }
+
+    fn run(&self, ennemy: &mut Ennemy, _memory: &mut AiMemory, context: &AiContext, _rng: &mut StdRng, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
+        ennemy.chase_player(context.player, collision_query);
+    }
+}
+
+pub struct Flank;
+
+impl AiNode for Flank {
+    fn name(&self) -> &'static str {
+        "flank"
+    }
+
+    fn score(&self, _ennemy: &Ennemy, _memory: &AiMemory, context: &AiContext) -> f32 {
+        if context.sees_player() && context.allies_chasing >= AI_FLANK_MIN_ALLIES && context.player_distance > AI_FLANK_MIN_DISTANCE {
+            0.75
+        } else {
+            0.
+        }
+    }
+
+    fn run(&self, ennemy: &mut Ennemy, _memory: &mut AiMemory, context: &AiContext, _rng: &mut StdRng, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
+        let (px, py) = context.player.get_pos();
+        let (dx, dy) = ((px - ennemy.x()) / context.player_distance, (py - ennemy.y()) / context.player_distance);
+        // Go around the player on the side we are already leaning towards
+        let left = (px - dy * AI_FLANK_OFFSET, py + dx * AI_FLANK_OFFSET);
+        let right = (px + dy * AI_FLANK_OFFSET, py - dx * AI_FLANK_OFFSET);
+        let target = if distance(ennemy.get_pos(), left) < distance(ennemy.get_pos(), right) { left } else { right };
+        ennemy.go_to(target.0, target.1, true, collision_query);
+    }
+}
+
+pub struct Retreat;
+
+impl AiNode for Retreat {
+    fn name(&self) -> &'static str {
+        "retreat"
+    }
+
+    fn score(&self, ennemy: &Ennemy, _memory: &AiMemory, context: &AiContext) -> f32 {
+        let health_ratio = ennemy.health() as f32 / ennemy.max_health() as f32;
+        if context.sees_player() && health_ratio <= AI_RETREAT_HEALTH_RATIO { 0.9 } else { 0. }
+    }
+
+    fn run(&self, ennemy: &mut Ennemy, _memory: &mut AiMemory, context: &AiContext, _rng: &mut StdRng, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
+        let (px, py) = context.player.get_pos();
+        let target = (2. * ennemy.x() - px, 2. * ennemy.y() - py);
+        ennemy.go_to(target.0, target.1, true, collision_query);
+    }
+}
+
+pub struct GuardShrine;
+
+impl AiNode for GuardShrine {
+    fn name(&self) -> &'static str {
+        "guard_shrine"
+    }
+
+    fn score(&self, ennemy: &Ennemy, memory: &AiMemory, context: &AiContext) -> f32 {
+        let post = memory.guard_post.or_else(|| nearest(ennemy.get_pos(), context.shrines));
+        match post {
+            Some(post) if distance(ennemy.get_pos(), post) > AI_GUARD_RADIUS => 0.5,
+            _ => 0.,
+        }
+    }
+
+    fn run(&self, ennemy: &mut Ennemy, memory: &mut AiMemory, context: &AiContext, _rng: &mut StdRng, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
+        if memory.guard_post.is_none() {
+            memory.guard_post = nearest(ennemy.get_pos(), context.shrines);
+        }
+        if let Some(post) = memory.guard_post {
+            ennemy.go_to(post.0, post.1, false, collision_query);
+        }
+    }
+}
+
+fn nearest(from: (f32, f32), points: &[(f32, f32)]) -> Option<(f32, f32)> {
+    points.iter()
+        .copied()
+        .min_by(|a, b| distance(from, *a).total_cmp(&distance(from, *b)))
+}
+
+fn think(
+    mut ennemy_query: Query<(&mut Ennemy, &mut Brain)>,
+    player_query: Query<&Player>,
+    sanctuary_query: Query<&Sanctuary>,
+    collision_query: Query<&CollisionComponent, Without<Ennemy>>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let player = player_query.single();
+    let shrines: Vec<(f32, f32)> = sanctuary_query.iter().map(|sanctuary| sanctuary.get_pos()).collect();
+    let allies_chasing = ennemy_query.iter().filter(|(ennemy, _)| ennemy.is_chasing()).count();
+
+    for (mut ennemy, mut brain) in ennemy_query.iter_mut() {
+        if ennemy.is_doing_something() {
+            continue;
+        }
+        let context = AiContext {
+            player,
+            player_distance: distance(ennemy.get_pos(), player.get_pos()),
+            allies_chasing,
+            shrines: &shrines,
+        };
+        if context.sees_player() {
+            brain.memory.last_seen_player = Some(player.get_pos());
+        }
+
+        let brain = &mut *brain;
+        if let Some(i) = brain.decide(&ennemy, &context) {
+            let node = &brain.nodes[i];
+            brain.current = Some(node.name());
+            node.run(&mut ennemy, &mut brain.memory, &context, &mut game_rng.ai, &collision_query);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::GameState;
+use crate::constants::*;
+use crate::tick::TickSet;
+use crate::ysort::YSort;
+
+// Sheets and clips of every animated sprite, see the comment at the top of the file
+const ANIMATIONS: &str = include_str!("../assets/animations.ron");
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AnimationLibrary>()
+            .add_event::<AnimationEventFired>()
+            .add_systems(FixedUpdate, animate_sprites.in_set(TickSet::Animation).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, (spawn_footstep_dust, fade_footstep_dust).run_if(in_state(GameState::Playing)));
+    }
+}
+
+#[derive(Deserialize)]
+struct SheetDef {
+    texture: String,
+    tile: (f32, f32),
+    columns: usize,
+    rows: usize,
+    #[serde(default)]
+    padding: (f32, f32),
+}
+
+// What gameplay, sounds and effects can wait for in a clip
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum AnimationEvent {
+    Hit,
+    Footstep,
+    // Sent by every clip that does not loop once it is over, never declared
+    End,
+}
+
+#[derive(Deserialize)]
+struct FrameEventDef {
+    frame: usize,
+    event: AnimationEvent,
+    #[serde(default)]
+    sound: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ClipDef {
+    sheet: String,
+    frames: Vec<usize>,
+    frame_duration: f32,
+    #[serde(default)]
+    looping: bool,
+    #[serde(default)]
+    events: Vec<FrameEventDef>,
+}
+
+#[derive(Deserialize)]
+struct AnimationsFile {
+    sheets: HashMap<String, SheetDef>,
+    clips: HashMap<String, ClipDef>,
+}
+
+struct FrameEvent {
+    frame: usize,
+    event: AnimationEvent,
+    sound: Option<Handle<AudioSource>>,
+}
+
+pub struct AnimationClip {
+    atlas: Handle<TextureAtlas>,
+    frames: Vec<usize>,
+    frame_duration: f32,
+    looping: bool,
+    events: Vec<FrameEvent>,
+}
+
+// Every clip by name, each sheet is loaded and cut only once for all the sprites using it
+#[derive(Resource)]
+pub struct AnimationLibrary {
+    clips: HashMap<String, AnimationClip>,
+}
+
+impl FromWorld for AnimationLibrary {
+    fn from_world(world: &mut World) -> Self {
+        let file: AnimationsFile = ron::from_str(ANIMATIONS).unwrap_or_else(|e| panic!("assets/animations.ron is not valid: {}", e));
+
+        let asset_server = world.resource::<AssetServer>().clone();
+        let mut texture_atlases = world.resource_mut::<Assets<TextureAtlas>>();
+        let mut atlases = HashMap::new();
+        for (name, sheet) in file.sheets {
+            let texture = asset_server.load(sheet.texture);
+            let atlas = TextureAtlas::from_grid(texture, Vec2::new(sheet.tile.0, sheet.tile.1), sheet.columns, sheet.rows, Some(Vec2::new(sheet.padding.0, sheet.padding.1)), None);
+            atlases.insert(name, (texture_atlases.add(atlas), sheet.columns * sheet.rows));
+        }
+
+        let mut clips = HashMap::new();
+        for (name, clip) in file.clips {
+            let Some((atlas, frame_count)) = atlases.get(&clip.sheet) else {
+                panic!("clip {} uses the sheet {} which does not exist", name, clip.sheet);
+            };
+            if clip.frames.is_empty() || clip.frames.iter().any(|&frame| frame >= *frame_count) {
+                panic!("clip {} has frames outside of the sheet {}", name, clip.sheet);
+            }
+            if clip.events.iter().any(|event| event.frame >= clip.frames.len() || event.event == AnimationEvent::End) {
+                panic!("clip {} declares End or an event outside of its frames", name);
+            }
+            let events = clip.events.into_iter().map(|event| FrameEvent {
+                frame: event.frame,
+                event: event.event,
+                sound: event.sound.map(|sound| asset_server.load(sound)),
+            }).collect();
+            clips.insert(name, AnimationClip {
+                atlas: atlas.clone(),
+                frames: clip.frames,
+                frame_duration: clip.frame_duration,
+                looping: clip.looping,
+                events,
+            });
+        }
+        AnimationLibrary { clips }
+    }
+}
+
+impl AnimationLibrary {
+    pub fn get(&self, name: &str) -> Option<&AnimationClip> {
+        self.clips.get(name)
+    }
+
+    // Atlas to spawn a sprite with before its first clip is played
+    pub fn atlas(&self, name: &str) -> Handle<TextureAtlas> {
+        self.get(name).map(|clip| clip.atlas.clone()).unwrap_or_default()
+    }
+}
+
+// For the systems outside of the ticks (sounds, effects), the tick systems read the Animator instead
+#[derive(Event, Clone, Debug)]
+pub struct AnimationEventFired {
+    pub entity: Entity,
+    pub clip: &'static str,
+    pub event: AnimationEvent,
+    pub sound: Option<Handle<AudioSource>>,
+}
+
+// Clip played by a sprite, moved forward once per tick
+#[derive(Component)]
+pub struct Animator {
+    clip: &'static str,
+    frame: usize,
+    elapsed: f32,
+    started: bool,
+    finished: bool,
+    // Events of the last tick, kept until the next one
+    fired: Vec<AnimationEvent>,
+}
+
+impl Animator {
+    pub fn new(clip: &'static str) -> Self {
+        Animator { clip, frame: 0, elapsed: 0., started: false, finished: false, fired: Vec::new() }
+    }
+
+    // Starts the clip from its first frame, unless it is already playing
+    pub fn play(&mut self, clip: &'static str) {
+        if self.clip != clip {
+            *self = Animator::new(clip);
+        }
+    }
+
+    // Whether this clip is the one played and sent this event during the last tick
+    pub fn fired(&self, clip: &str, event: AnimationEvent) -> bool {
+        self.clip == clip && self.fired.contains(&event)
+    }
+}
+
+fn animate_sprites(
+    library: Res<AnimationLibrary>,
+    mut query: Query<(Entity, &mut Animator, &mut TextureAtlasSprite, &mut Handle<TextureAtlas>)>,
+    mut fired_events: EventWriter<AnimationEventFired>,
+) {
+    for (entity, mut animator, mut sprite, mut atlas) in query.iter_mut() {
+        animator.fired.clear();
+        let Some(clip) = library.get(animator.clip) else {
+            continue;
+        };
+        if *atlas != clip.atlas {
+            *atlas = clip.atlas.clone();
+        }
+
+        // Frame shown for the first time this tick, if any
+        let mut entered = None;
+        if !animator.started {
+            animator.started = true;
+            entered = Some(0);
+        } else if !animator.finished {
+            animator.elapsed += FIXED_TIMESTEP;
+            if animator.elapsed + TIMESTEP_EPSILON >= clip.frame_duration {
+                // The remainder carries over, so the clip keeps its pace when frames are not whole ticks
+                animator.elapsed = (animator.elapsed - clip.frame_duration).max(0.);
+                animator.frame += 1;
+                if animator.frame < clip.frames.len() {
+                    entered = Some(animator.frame);
+                } else if clip.looping {
+                    animator.frame = 0;
+                    entered = Some(0);
+                } else {
+                    animator.frame = clip.frames.len() - 1;
+                    animator.finished = true;
+                    animator.fired.push(AnimationEvent::End);
+                    fired_events.send(AnimationEventFired { entity, clip: animator.clip, event: AnimationEvent::End, sound: None });
+                }
+            }
+        }
+        if let Some(frame) = entered {
+            for frame_event in clip.events.iter().filter(|frame_event| frame_event.frame == frame) {
+                animator.fired.push(frame_event.event);
+                fired_events.send(AnimationEventFired { entity, clip: animator.clip, event: frame_event.event, sound: frame_event.sound.clone() });
+            }
+        }
+        sprite.index = clip.frames[animator.frame];
+    }
+}
+
+// Small puff left on the ground at each step
+#[derive(Component)]
+struct FootstepDust {
+    timer: Timer,
+}
+
+fn spawn_footstep_dust(
+    mut commands: Commands,
+    mut fired_events: EventReader<AnimationEventFired>,
+    feet_query: Query<(&Transform, &YSort)>,
+) {
+    for fired in fired_events.iter().filter(|fired| fired.event == AnimationEvent::Footstep) {
+        let Ok((transform, ysort)) = feet_query.get(fired.entity) else {
+            continue;
+        };
+        commands.spawn((SpriteBundle {
+            transform: Transform::from_xyz(transform.translation.x, transform.translation.y - ysort.foot_offset(), Z_LAYER_DECALS),
+            sprite: Sprite {
+                color: Color::rgba(0.8, 0.7, 0.55, FOOTSTEP_DUST_ALPHA),
+                custom_size: Some(Vec2::new(FOOTSTEP_DUST_WIDTH, FOOTSTEP_DUST_HEIGHT)),
+                ..Default::default()
+            },
+            ..Default::default()
+        }, FootstepDust { timer: Timer::from_seconds(FOOTSTEP_DUST_DURATION, TimerMode::Once) }));
+    }
+}
+
+fn fade_footstep_dust(
+    mut commands: Commands,
+    mut dust_query: Query<(Entity, &mut FootstepDust, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut dust, mut sprite) in dust_query.iter_mut() {
+        dust.timer.tick(time.delta());
+        if dust.timer.finished() {
+            commands.entity(entity).despawn();
+        } else {
+            sprite.color.set_a(FOOTSTEP_DUST_ALPHA * dust.timer.percent_left());
+        }
+    }
+}
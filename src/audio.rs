@@ -0,0 +1,332 @@
+use bevy::audio::{AudioSinkPlayback, Volume};
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use serde::{Deserialize, Serialize};
+
+use crate::GameState;
+use crate::animation::AnimationEventFired;
+use crate::constants::*;
+use crate::ennemies::Ennemy;
+use crate::events::*;
+use crate::settings::Settings;
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundEffects>()
+            .init_resource::<MusicDirector>()
+            .add_systems(Update, (choose_music, fade_music).chain())
+            .add_systems(Update, play_sound_effects)
+            .add_systems(Update, (drag_volume_sliders, update_volume_fills).chain().run_if(in_state(GameState::Paused).or_else(in_state(GameState::Settings))));
+    }
+}
+
+// Saved with the other settings, see settings.rs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            master: AUDIO_DEFAULT_MASTER_VOLUME,
+            music: AUDIO_DEFAULT_MUSIC_VOLUME,
+            sfx: AUDIO_DEFAULT_SFX_VOLUME,
+        }
+    }
+}
+
+impl AudioSettings {
+    fn get(&self, channel: VolumeChannel) -> f32 {
+        match channel {
+            VolumeChannel::Master => self.master,
+            VolumeChannel::Music => self.music,
+            VolumeChannel::Sfx => self.sfx,
+        }
+    }
+
+    fn set(&mut self, channel: VolumeChannel, value: f32) {
+        let value = value.clamp(0., 1.);
+        match channel {
+            VolumeChannel::Master => self.master = value,
+            VolumeChannel::Music => self.music = value,
+            VolumeChannel::Sfx => self.sfx = value,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MusicKind {
+    Menu,
+    Exploration,
+    Combat,
+    GameOver,
+    Victory,
+}
+
+impl MusicKind {
+    fn path(&self) -> &'static str {
+        match self {
+            MusicKind::Menu => "Audio/music/menu.wav",
+            MusicKind::Exploration => "Audio/music/exploration.wav",
+            MusicKind::Combat => "Audio/music/combat.wav",
+            MusicKind::GameOver => "Audio/music/gameover.wav",
+            MusicKind::Victory => "Audio/music/victory.wav",
+        }
+    }
+}
+
+// A playing track fades towards full volume while it is the wanted one, then fades out and is despawned
+#[derive(Component)]
+struct MusicTrack {
+    kind: MusicKind,
+    level: f32,
+    fading_in: bool,
+}
+
+#[derive(Resource, Default)]
+struct MusicDirector {
+    combat_linger: f32,
+}
+
+#[derive(Resource)]
+struct SoundEffects {
+    swing: Handle<AudioSource>,
+    block: Handle<AudioSource>,
+    enemy_hit: Handle<AudioSource>,
+    enemy_death: Handle<AudioSource>,
+    player_hurt: Handle<AudioSource>,
+    shrine_unlock: Handle<AudioSource>,
+    tower_activate: Handle<AudioSource>,
+}
+
+impl FromWorld for SoundEffects {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        SoundEffects {
+            swing: asset_server.load("Audio/sfx/swing.wav"),
+            block: asset_server.load("Audio/sfx/block.wav"),
+            enemy_hit: asset_server.load("Audio/sfx/enemy_hit.wav"),
+            enemy_death: asset_server.load("Audio/sfx/enemy_death.wav"),
+            player_hurt: asset_server.load("Audio/sfx/player_hurt.wav"),
+            shrine_unlock: asset_server.load("Audio/sfx/shrine_unlock.wav"),
+            tower_activate: asset_server.load("Audio/sfx/tower_activate.wav"),
+        }
+    }
+}
+
+fn choose_music(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    ennemy_query: Query<&Ennemy>,
+    mut track_query: Query<&mut MusicTrack>,
+    mut director: ResMut<MusicDirector>,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+) {
+    let wanted = match state.get() {
+        GameState::Menu | GameState::Settings | GameState::Loading | GameState::Editor => MusicKind::Menu,
+        GameState::Playing => {
+            // Stay on the combat music a little while after the last chase to avoid switching back and forth
+            if ennemy_query.iter().any(|ennemy| ennemy.is_chasing()) {
+                director.combat_linger = AUDIO_COMBAT_LINGER;
+            } else {
+                director.combat_linger = (director.combat_linger - time.delta_seconds()).max(0.);
+            }
+            if director.combat_linger > 0. { MusicKind::Combat } else { MusicKind::Exploration }
+        },
+        GameState::Paused => return,
+        GameState::GameOver => MusicKind::GameOver,
+        GameState::Victory => MusicKind::Victory,
+    };
+
+    if track_query.iter().any(|track| track.kind == wanted && track.fading_in) {
+        return;
+    }
+    for mut track in track_query.iter_mut() {
+        track.fading_in = false;
+    }
+    commands.spawn((AudioBundle {
+        source: asset_server.load(wanted.path()),
+        settings: PlaybackSettings::LOOP.with_volume(Volume::new_relative(0.)),
+    }, MusicTrack { kind: wanted, level: 0., fading_in: true }));
+}
+
+fn fade_music(
+    mut commands: Commands,
+    mut track_query: Query<(Entity, &mut MusicTrack, Option<&AudioSink>)>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let settings = &settings.audio;
+    let step = time.delta_seconds() / AUDIO_CROSSFADE_DURATION;
+    for (entity, mut track, sink) in track_query.iter_mut() {
+        track.level = if track.fading_in { (track.level + step).min(1.) } else { (track.level - step).max(0.) };
+        if !track.fading_in && track.level <= 0. {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(sink) = sink {
+            sink.set_volume(settings.master * settings.music * track.level);
+        }
+    }
+}
+
+fn play_sound(commands: &mut Commands, sound: &Handle<AudioSource>, settings: &AudioSettings) {
+    commands.spawn(AudioBundle {
+        source: sound.clone(),
+        settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_relative(settings.master * settings.sfx)),
+    });
+}
+
+fn play_sound_effects(
+    mut commands: Commands,
+    sounds: Res<SoundEffects>,
+    settings: Res<Settings>,
+    mut player_attacked: EventReader<PlayerAttacked>,
+    mut player_damaged: EventReader<PlayerDamaged>,
+    mut player_blocked: EventReader<PlayerBlocked>,
+    mut enemy_damaged: EventReader<EnemyDamaged>,
+    mut enemy_blocked: EventReader<EnemyBlocked>,
+    mut enemy_killed: EventReader<EnemyKilled>,
+    mut shrine_unlocked: EventReader<ShrineUnlocked>,
+    mut tower_activated: EventReader<TowerActivated>,
+    mut animation_events: EventReader<AnimationEventFired>,
+) {
+    let settings = &settings.audio;
+    for _ in player_attacked.iter() {
+        play_sound(&mut commands, &sounds.swing, &settings);
+    }
+    for _ in player_damaged.iter() {
+        play_sound(&mut commands, &sounds.player_hurt, &settings);
+    }
+    for _ in player_blocked.iter() {
+        play_sound(&mut commands, &sounds.block, &settings);
+    }
+    for _ in enemy_blocked.iter() {
+        play_sound(&mut commands, &sounds.block, &settings);
+    }
+    for _ in enemy_damaged.iter() {
+        play_sound(&mut commands, &sounds.enemy_hit, &settings);
+    }
+    for _ in enemy_killed.iter() {
+        play_sound(&mut commands, &sounds.enemy_death, &settings);
+    }
+    for _ in shrine_unlocked.iter() {
+        play_sound(&mut commands, &sounds.shrine_unlock, &settings);
+    }
+    for _ in tower_activated.iter().filter(|event| event.activation.is_success()) {
+        play_sound(&mut commands, &sounds.tower_activate, &settings);
+    }
+    // Sounds given to the events of the clips in assets/animations.ron
+    for sound in animation_events.iter().filter_map(|event| event.sound.as_ref()) {
+        play_sound(&mut commands, sound, &settings);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VolumeChannel {
+    Master,
+    Music,
+    Sfx,
+}
+
+#[derive(Component)]
+struct VolumeSlider(VolumeChannel);
+
+#[derive(Component)]
+struct VolumeFill(VolumeChannel);
+
+pub fn create_volume_sliders(
+    commands: &mut ChildBuilder,
+    settings: &Settings,
+) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            for (label, channel) in [("Master", VolumeChannel::Master), ("Music", VolumeChannel::Music), ("SFX", VolumeChannel::Sfx)] {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(20.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(TextBundle {
+                            text: Text::from_section(settings.language.tr(label), TextStyle {
+                                font_size: 30.0,
+                                color: Color::WHITE,
+                                ..Default::default()
+                            }),
+                            style: Style {
+                                width: Val::Px(120.0),
+                                ..default()
+                            },
+                            ..Default::default()
+                        });
+                        row.spawn((ButtonBundle {
+                            style: Style {
+                                width: Val::Px(300.0),
+                                height: Val::Px(20.0),
+                                ..default()
+                            },
+                            background_color: Color::rgb(0.2, 0.2, 0.2).into(),
+                            ..default()
+                        }, RelativeCursorPosition::default(), VolumeSlider(channel)))
+                        .with_children(|slider| {
+                            slider.spawn((NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(settings.audio.get(channel) * 100.),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                background_color: Color::rgb(0.8, 0.7, 0.3).into(),
+                                ..default()
+                            }, VolumeFill(channel)));
+                        });
+                    });
+            }
+        });
+}
+
+fn drag_volume_sliders(
+    slider_query: Query<(&Interaction, &RelativeCursorPosition, &VolumeSlider)>,
+    mut settings: ResMut<Settings>,
+) {
+    for (interaction, cursor, slider) in slider_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if let Some(position) = cursor.normalized {
+            settings.audio.set(slider.0, position.x);
+        }
+    }
+}
+
+fn update_volume_fills(
+    mut fill_query: Query<(&mut Style, &VolumeFill)>,
+    settings: Res<Settings>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut style, fill) in fill_query.iter_mut() {
+        style.width = Val::Percent(settings.audio.get(fill.0) * 100.);
+    }
+}
@@ -0,0 +1,297 @@
+use bevy::prelude::*;
+
+use crate::{GameConfig, GameState};
+use crate::constants::*;
+use crate::collisions::*;
+use crate::ennemies::*;
+use crate::entitypattern::{EntityBehavior, FacingDirection};
+use crate::player::Player;
+use crate::rng::GameRng;
+use crate::structures::{Tower, BossProgress};
+use crate::tick::*;
+use crate::events::*;
+use crate::ysort::YSort;
+use crate::animation::{AnimationLibrary, Animator};
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, (spawn_boss_near_tower,
+                                      boss_behaviour,
+                                      boss_area_attack,
+                                      boss_defeat_detection,
+                                     ).chain().in_set(TickSet::Boss).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, update_boss_health_bar.run_if(in_state(GameState::Playing)));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BossPhase {
+    Charge,
+    Summon,
+    Area,
+}
+
+#[derive(Component)]
+pub struct Boss {
+    tower: Entity,
+    phase: BossPhase,
+    action_timer: Timer,
+    charge: Option<(FacingDirection, Timer)>,
+    area_windup: Option<Timer>,
+}
+
+impl Boss {
+    pub fn new(tower: Entity) -> Self {
+        Boss {
+            tower,
+            phase: BossPhase::Charge,
+            action_timer: Timer::from_seconds(BOSS_CHARGE_COOLDOWN, TimerMode::Once),
+            charge: None,
+            area_windup: None,
+        }
+    }
+
+    // From the health of its Ennemy, already scaled by the difficulty
+    fn update_phase(&mut self, ennemy: &Ennemy) {
+        let ratio = ennemy.health() as f32 / ennemy.max_health() as f32;
+        self.phase = if ratio > 2. / 3. {
+            BossPhase::Charge
+        } else if ratio > 1. / 3. {
+            BossPhase::Summon
+        } else {
+            BossPhase::Area
+        };
+    }
+
+    fn is_busy(&self) -> bool {
+        self.charge.is_some() || self.area_windup.is_some()
+    }
+}
+
+#[derive(Component)]
+pub struct BossAreaTelegraph;
+
+#[derive(Component)]
+pub struct BossHealthBar;
+
+#[derive(Component)]
+pub struct BossHealthFill;
+
+fn direction_towards(dx: f32, dy: f32) -> FacingDirection {
+    let horizontal = dx.abs() > f32::EPSILON && dx.abs() * 2. > dy.abs();
+    let vertical = dy.abs() > f32::EPSILON && dy.abs() * 2. > dx.abs();
+    match (horizontal, vertical, dx > 0., dy > 0.) {
+        (true, true, true, true) => FacingDirection::TopRight,
+        (true, true, false, true) => FacingDirection::TopLeft,
+        (true, true, true, false) => FacingDirection::BottomRight,
+        (true, true, false, false) => FacingDirection::BottomLeft,
+        (true, false, true, _) => FacingDirection::Right,
+        (true, false, false, _) => FacingDirection::Left,
+        (_, _, _, true) => FacingDirection::Up,
+        _ => FacingDirection::Down,
+    }
+}
+
+fn spawn_boss_near_tower(
+    mut commands: Commands,
+    mut tower_query: Query<(Entity, &mut Tower)>,
+    animation_library: Res<AnimationLibrary>,
+    mut spawned_events: EventWriter<BossSpawned>,
+) {
+    for (tower_entity, mut tower) in tower_query.iter_mut() {
+        if tower.boss_progress() != BossProgress::Summoned {
+            continue;
+        }
+        tower.set_boss_progress(BossProgress::Fighting);
+
+        let (x, y) = tower.get_pos();
+        let y = y - TOWER_HEIGHT / 2. - BOSS_HITBOX_HEIGHT;
+        let boss = Ennemy::new_with_hitbox(x, y, BOSS_HITBOX_WIDTH, BOSS_HITBOX_HEIGHT, BOSS_HEALTH, BOSS_ATTACK, BOSS_DEFENCE_RATIO);
+        let hitbox = CollisionComponent::new_from_component(&boss);
+
+        let entity = commands.spawn((SpriteSheetBundle {
+            texture_atlas: animation_library.atlas("skeleton_idle"),
+            transform: Transform {
+                translation: Vec3::new(x, y, Z_LAYER_WORLD),
+                scale: Vec3::new(BOSS_SPRITE_SCALE, BOSS_SPRITE_SCALE, 1.),
+                ..Default::default()
+            },
+            sprite: TextureAtlasSprite {
+                index: 0,
+                color: Color::rgb(1.0, 0.5, 0.5),
+                ..Default::default()
+            },
+            ..Default::default()
+        }, SimPosition::new(x, y), boss, hitbox, Boss::new(tower_entity), YSort::new(BOSS_HITBOX_HEIGHT / 2.), Animator::new("skeleton_idle"))).id();
+
+        commands
+            .spawn((NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.0),
+                    left: Val::Percent(25.0),
+                    width: Val::Percent(50.0),
+                    height: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            }, BossHealthBar))
+            .with_children(|parent| {
+                parent.spawn((NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.8, 0.1, 0.1).into(),
+                    ..default()
+                }, BossHealthFill));
+            });
+        spawned_events.send(BossSpawned { entity });
+    }
+}
+
+fn boss_behaviour(
+    mut commands: Commands,
+    mut boss_query: Query<(&mut Boss, &mut Ennemy)>,
+    player_query: Query<&Player>,
+    collision_query: Query<&CollisionComponent, Without<Ennemy>>,
+    animation_library: Res<AnimationLibrary>,
+) {
+    let player = player_query.single();
+    for (mut boss, mut ennemy) in boss_query.iter_mut() {
+        if ennemy.is_dying() || ennemy.is_dead() {
+            continue;
+        }
+        boss.update_phase(&ennemy);
+
+        if let Some((direction, mut timer)) = boss.charge.take() {
+            timer.tick(tick_duration());
+            let moved = ennemy.move_in_direction(&direction, BOSS_CHARGE_SPEED * FIXED_TIMESTEP, &collision_query);
+            if moved && !timer.finished() {
+                boss.charge = Some((direction, timer));
+            }
+            continue;
+        }
+
+        if boss.is_busy() {
+            continue;
+        }
+
+        boss.action_timer.tick(tick_duration());
+        if boss.action_timer.finished() && player.is_aggroable() {
+            let (px, py) = player.get_pos();
+            match boss.phase {
+                BossPhase::Charge => {
+                    let direction = direction_towards(px - ennemy.x(), py - ennemy.y());
+                    boss.charge = Some((direction, Timer::from_seconds(BOSS_CHARGE_DURATION, TimerMode::Once)));
+                    boss.action_timer = Timer::from_seconds(BOSS_CHARGE_COOLDOWN, TimerMode::Once);
+                },
+                BossPhase::Summon => {
+                    for i in 0..BOSS_SUMMON_NUMBER {
+                        let angle = i as f32 * std::f32::consts::TAU / BOSS_SUMMON_NUMBER as f32;
+                        let x = ennemy.x() + angle.cos() * BOSS_SUMMON_DISTANCE;
+                        let y = ennemy.y() + angle.sin() * BOSS_SUMMON_DISTANCE;
+                        spawn_ennemy(&mut commands, &animation_library, x, y);
+                    }
+                    boss.action_timer = Timer::from_seconds(BOSS_SUMMON_COOLDOWN, TimerMode::Once);
+                },
+                BossPhase::Area => {
+                    commands.spawn((SpriteBundle {
+                        transform: Transform::from_xyz(ennemy.x(), ennemy.y(), Z_LAYER_DECALS),
+                        sprite: Sprite {
+                            color: Color::rgba(1.0, 0.0, 0.0, 0.3),
+                            custom_size: Some(Vec2::new(BOSS_AREA_RADIUS * 2., BOSS_AREA_RADIUS * 2.)),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }, BossAreaTelegraph));
+                    boss.area_windup = Some(Timer::from_seconds(BOSS_AREA_WINDUP, TimerMode::Once));
+                    boss.action_timer = Timer::from_seconds(BOSS_AREA_COOLDOWN, TimerMode::Once);
+                },
+            }
+            continue;
+        }
+
+        if player.is_aggroable() && !ennemy.is_doing_something() {
+            ennemy.chase_player(player, &collision_query);
+        }
+    }
+}
+
+fn boss_area_attack(
+    mut commands: Commands,
+    mut boss_query: Query<(Entity, &mut Boss, &Ennemy)>,
+    mut player_query: Query<&mut Player>,
+    telegraph_query: Query<Entity, With<BossAreaTelegraph>>,
+    mut game_rng: ResMut<GameRng>,
+    game_config: Res<GameConfig>,
+    mut damaged_events: EventWriter<PlayerDamaged>,
+    mut area_events: EventWriter<BossAreaAttack>,
+) {
+    let mut player = player_query.single_mut();
+    // Scaled like the attack of the enemies
+    let damage = (BOSS_AREA_DAMAGE as f32 * game_config.difficulty.ennemy_multiplier()).round() as i32;
+    for (boss_entity, mut boss, ennemy) in boss_query.iter_mut() {
+        let Some(timer) = boss.area_windup.as_mut() else { continue };
+        timer.tick(tick_duration());
+        if !timer.finished() {
+            continue;
+        }
+        boss.area_windup = None;
+        for entity in telegraph_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        if ennemy.is_dying() || ennemy.is_dead() {
+            continue;
+        }
+        area_events.send(BossAreaAttack { entity: boss_entity });
+        let (px, py) = player.get_pos();
+        let distance = ((px - ennemy.x()).powi(2) + (py - ennemy.y()).powi(2)).sqrt();
+        if distance < BOSS_AREA_RADIUS && player.get_attacked(damage, &mut game_rng.combat) {
+            damaged_events.send(PlayerDamaged { damage, health: player.health() });
+        }
+    }
+}
+
+fn boss_defeat_detection(
+    mut commands: Commands,
+    boss_query: Query<(Entity, &Boss, &Ennemy)>,
+    mut tower_query: Query<&mut Tower>,
+    bar_query: Query<Entity, With<BossHealthBar>>,
+    telegraph_query: Query<Entity, With<BossAreaTelegraph>>,
+    mut defeated_events: EventWriter<BossDefeated>,
+) {
+    for (entity, boss, ennemy) in boss_query.iter() {
+        if !ennemy.is_dying() && !ennemy.is_dead() {
+            continue;
+        }
+        if let Ok(mut tower) = tower_query.get_mut(boss.tower) {
+            if tower.boss_progress() != BossProgress::Defeated {
+                tower.set_boss_progress(BossProgress::Defeated);
+                defeated_events.send(BossDefeated { entity });
+                for entity in bar_query.iter() {
+                    commands.entity(entity).despawn_recursive();
+                }
+                for entity in telegraph_query.iter() {
+                    commands.entity(entity).despawn();
+                }
+            }
+        }
+    }
+}
+
+fn update_boss_health_bar(
+    boss_query: Query<&Ennemy, With<Boss>>,
+    mut fill_query: Query<&mut Style, With<BossHealthFill>>,
+) {
+    for ennemy in boss_query.iter() {
+        let ratio = (ennemy.health().max(0) as f32 / ennemy.max_health() as f32).clamp(0., 1.);
+        for mut style in fill_query.iter_mut() {
+            style.width = Val::Percent(ratio * 100.);
+        }
+    }
+}
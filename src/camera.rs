@@ -0,0 +1,208 @@
+use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
+use bevy::window::PrimaryWindow;
+use rand::Rng;
+
+use crate::GameState;
+use crate::constants::*;
+use crate::entitypattern::{EntityBehavior, FacingDirection};
+use crate::events::*;
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::tick::interpolate_positions;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (apply_scaling_mode.run_if(resource_changed::<Settings>()),
+                                 update_ui_scale))
+            .add_systems(Update, (change_zoom_level,
+                                  add_trauma_on_hits,
+                                  follow_player).chain().after(interpolate_positions).run_if(in_state(GameState::Playing)));
+    }
+}
+
+// The camera keeps the player inside its dead zone, slides towards where they are facing and eases into place
+#[derive(Component)]
+pub struct CameraController {
+    pub dead_zone: Vec2,
+    pub damping: f32,
+    pub look_ahead: f32,
+    center: Vec2,
+    zoom_level: usize,
+    zoom: f32,
+    trauma: f32,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        CameraController {
+            dead_zone: Vec2::new(CAMERA_DEAD_ZONE_WIDTH, CAMERA_DEAD_ZONE_HEIGHT),
+            damping: CAMERA_DAMPING,
+            look_ahead: CAMERA_LOOK_AHEAD,
+            center: Vec2::ZERO,
+            zoom_level: CAMERA_DEFAULT_ZOOM_LEVEL,
+            zoom: CAMERA_ZOOM_LEVELS[CAMERA_DEFAULT_ZOOM_LEVEL],
+            trauma: 0.,
+        }
+    }
+}
+
+impl CameraController {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.);
+    }
+
+    fn set_zoom_level(&mut self, level: usize) {
+        self.zoom_level = level.min(CAMERA_ZOOM_LEVELS.len() - 1);
+        self.zoom = CAMERA_ZOOM_LEVELS[self.zoom_level];
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(CAMERA_MIN_SCALE, CAMERA_MAX_SCALE);
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn reset_zoom(&mut self) {
+        self.set_zoom_level(CAMERA_DEFAULT_ZOOM_LEVEL);
+    }
+}
+
+fn direction_vector(direction: FacingDirection) -> Vec2 {
+    let vector = match direction {
+        FacingDirection::Up => Vec2::new(0., 1.),
+        FacingDirection::Down => Vec2::new(0., -1.),
+        FacingDirection::Left => Vec2::new(-1., 0.),
+        FacingDirection::Right => Vec2::new(1., 0.),
+        FacingDirection::TopLeft => Vec2::new(-1., 1.),
+        FacingDirection::TopRight => Vec2::new(1., 1.),
+        FacingDirection::BottomLeft => Vec2::new(-1., -1.),
+        FacingDirection::BottomRight => Vec2::new(1., -1.),
+    };
+    vector.normalize()
+}
+
+// With the fixed view, the camera shows at least the reference resolution whatever the window size
+pub fn scaling_mode(settings: &Settings) -> ScalingMode {
+    if settings.fixed_view {
+        ScalingMode::AutoMin { min_width: WINDOW_WIDTH, min_height: WINDOW_HEIGHT }
+    } else {
+        ScalingMode::WindowSize(1.)
+    }
+}
+
+// Half of the world area seen by the camera, kept up to date by bevy when the window is resized
+pub fn view_half_size(projection: &OrthographicProjection) -> Vec2 {
+    projection.area.half_size()
+}
+
+fn apply_scaling_mode(
+    settings: Res<Settings>,
+    mut projection_query: Query<&mut OrthographicProjection, With<Camera>>,
+) {
+    for mut projection in projection_query.iter_mut() {
+        projection.scaling_mode = scaling_mode(&settings);
+    }
+}
+
+// The menus are laid out for the reference resolution and scaled to fit the window
+fn update_ui_scale(
+    mut ui_scale: ResMut<UiScale>,
+    window_query: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let scale = (window.width() / WINDOW_WIDTH).min(window.height() / WINDOW_HEIGHT) as f64;
+    if scale > 0. && ui_scale.scale != scale {
+        ui_scale.scale = scale;
+    }
+}
+
+fn change_zoom_level(
+    mut controller_query: Query<&mut CameraController>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    for mut controller in controller_query.iter_mut() {
+        let level = controller.zoom_level;
+        if keyboard_input.any_just_pressed([KeyCode::Minus, KeyCode::NumpadSubtract]) {
+            controller.set_zoom_level(level + 1);
+        }
+        if keyboard_input.any_just_pressed([KeyCode::Equals, KeyCode::NumpadAdd]) {
+            controller.set_zoom_level(level.saturating_sub(1));
+        }
+    }
+}
+
+fn add_trauma_on_hits(
+    mut controller_query: Query<&mut CameraController>,
+    mut player_damaged: EventReader<PlayerDamaged>,
+    mut enemy_damaged: EventReader<EnemyDamaged>,
+    mut enemy_killed: EventReader<EnemyKilled>,
+    mut boss_area_attacks: EventReader<BossAreaAttack>,
+) {
+    let trauma = player_damaged.iter().count() as f32 * CAMERA_TRAUMA_PLAYER_HIT
+        + (enemy_damaged.iter().count() + enemy_killed.iter().count()) as f32 * CAMERA_TRAUMA_ENEMY_HIT
+        + boss_area_attacks.iter().count() as f32 * CAMERA_TRAUMA_BOSS_AREA;
+    if trauma <= 0. {
+        return;
+    }
+    for mut controller in controller_query.iter_mut() {
+        controller.add_trauma(trauma);
+    }
+}
+
+fn follow_player(
+    player_query: Query<(&Transform, Ref<Player>), Without<CameraController>>,
+    mut camera_query: Query<(&mut CameraController, &mut Transform, &mut OrthographicProjection)>,
+    time: Res<Time>,
+) {
+    let Ok((player_transform, player)) = player_query.get_single() else {
+        return;
+    };
+    let dt = time.delta_seconds();
+    for (mut controller, mut transform, mut projection) in camera_query.iter_mut() {
+        let look_ahead = player.facing_direction().map(direction_vector).unwrap_or(Vec2::ZERO) * controller.look_ahead;
+        let focus = player_transform.translation.truncate() + look_ahead;
+
+        // A new run starts with the camera already on the player
+        if player.is_added() {
+            controller.center = focus;
+        }
+
+        let offset = focus - controller.center;
+        let outside = (offset.abs() - controller.dead_zone).max(Vec2::ZERO) * offset.signum();
+        let smoothing = 1. - (-controller.damping * dt).exp();
+        controller.center += outside * smoothing;
+
+        // A window bigger than the largest resolution sees the same area as it, the spawn director counts on it
+        let area_per_scale = view_half_size(&projection) / projection.scale;
+        let target = if area_per_scale.min_element() > 0. {
+            let max_half_size = Vec2::new(VIEW_MAX_WIDTH, VIEW_MAX_HEIGHT) / 2. * controller.zoom;
+            controller.zoom.min((max_half_size / area_per_scale).min_element())
+        } else {
+            controller.zoom
+        };
+        projection.scale += (target - projection.scale) * smoothing;
+        let limit = (Vec2::splat(MAP_SIZE / 2.) - view_half_size(&projection)).max(Vec2::ZERO);
+        controller.center = controller.center.clamp(-limit, limit);
+
+        // Shake grows with the square of the trauma so that small hits stay subtle
+        controller.trauma = (controller.trauma - CAMERA_TRAUMA_DECAY * dt).max(0.);
+        let shake = controller.trauma * controller.trauma;
+        let mut rng = rand::thread_rng();
+        let shake_offset = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * CAMERA_SHAKE_MAX_OFFSET * shake;
+        let shake_angle = rng.gen_range(-1.0..1.0) * CAMERA_SHAKE_MAX_ANGLE * shake;
+
+        transform.translation.x = controller.center.x + shake_offset.x;
+        transform.translation.y = controller.center.y + shake_offset.y;
+        transform.rotation = Quat::from_rotation_z(shake_angle);
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::GameConfig;
+use crate::GameState;
+use crate::ai::Brain;
+use crate::animation::AnimationLibrary;
+use crate::boss::Boss;
+use crate::constants::*;
+use crate::director::direct_spawns;
+use crate::ennemies::{Ennemy, chunk_ennemies, spawn_ennemy, spawn_ennemy_from};
+use crate::entitypattern::EntityBehavior;
+use crate::player::Player;
+use crate::setup::{PropAtlases, spawn_prop};
+use crate::tick::TickSet;
+use crate::tilemap::{TileAssets, TileMap, spawn_chunk_tiles};
+use crate::worldgen::WorldPlan;
+
+pub struct ChunkPlugin;
+
+impl Plugin for ChunkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadedChunks>()
+            .add_systems(OnTransition { from: GameState::Loading, to: GameState::Playing }, reset_chunks)
+            .add_systems(FixedUpdate, stream_chunks.in_set(TickSet::Director).before(direct_spawns).run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Chunk (0, 0) is the bottom left corner of the map
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct ChunkCoord {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl ChunkCoord {
+    pub fn all() -> impl Iterator<Item = ChunkCoord> {
+        (0..WORLD_CHUNKS).flat_map(|y| (0..WORLD_CHUNKS).map(move |x| ChunkCoord { x, y }))
+    }
+
+    pub fn at(x: f32, y: f32) -> Option<Self> {
+        let chunk_x = ((x + MAP_SIZE / 2.) / CHUNK_SIZE).floor();
+        let chunk_y = ((y + MAP_SIZE / 2.) / CHUNK_SIZE).floor();
+        if chunk_x < 0. || chunk_y < 0. || chunk_x >= WORLD_CHUNKS as f32 || chunk_y >= WORLD_CHUNKS as f32 {
+            return None;
+        }
+        Some(ChunkCoord { x: chunk_x as usize, y: chunk_y as usize })
+    }
+
+    pub fn center(&self) -> (f32, f32) {
+        (self.x as f32 * CHUNK_SIZE - MAP_SIZE / 2. + CHUNK_SIZE / 2.,
+         self.y as f32 * CHUNK_SIZE - MAP_SIZE / 2. + CHUNK_SIZE / 2.)
+    }
+
+    pub fn first_tile(&self) -> (usize, usize) {
+        (self.x * CHUNK_TILES, self.y * CHUNK_TILES)
+    }
+
+    // Whether a hitbox centered on (x, y) is entirely inside the chunk
+    pub fn contains_area(&self, x: f32, y: f32, w: f32, h: f32) -> bool {
+        let (center_x, center_y) = self.center();
+        (x - center_x).abs() + w / 2. < CHUNK_SIZE / 2. && (y - center_y).abs() + h / 2. < CHUNK_SIZE / 2.
+    }
+
+    // Everything random in a chunk comes from here, so that it does not depend on the order chunks are visited in
+    pub fn rng(&self, seed: u64) -> StdRng {
+        StdRng::seed_from_u64(seed ^ (((self.y * WORLD_CHUNKS + self.x) as u64 + 1) << 32))
+    }
+
+    fn distance(&self, other: ChunkCoord) -> usize {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    fn around(&self, radius: usize) -> impl Iterator<Item = ChunkCoord> + '_ {
+        ChunkCoord::all().filter(move |chunk| chunk.distance(*self) <= radius)
+    }
+}
+
+// Tiles, collisions and props that go away with their chunk
+#[derive(Component)]
+pub struct ChunkMember(pub ChunkCoord);
+
+struct StoredEnnemy {
+    ennemy: Ennemy,
+    guard: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct LoadedChunks {
+    loaded: BTreeSet<ChunkCoord>,
+    visited: BTreeSet<ChunkCoord>,
+    // Enemies waiting in unloaded chunks, spawned back as they were when the chunk loads again
+    stored: BTreeMap<ChunkCoord, Vec<StoredEnnemy>>,
+}
+
+impl LoadedChunks {
+    pub fn is_loaded(&self, chunk: ChunkCoord) -> bool {
+        self.loaded.contains(&chunk)
+    }
+}
+
+fn reset_chunks(mut chunks: ResMut<LoadedChunks>) {
+    *chunks = LoadedChunks::default();
+}
+
+fn stream_chunks(
+    mut commands: Commands,
+    mut chunks: ResMut<LoadedChunks>,
+    player_query: Query<&Player>,
+    member_query: Query<(Entity, &ChunkMember)>,
+    ennemy_query: Query<(Entity, &Ennemy, &Brain), Without<Boss>>,
+    tile_map: Res<TileMap>,
+    plan: Res<WorldPlan>,
+    tile_assets: Res<TileAssets>,
+    prop_atlases: Res<PropAtlases>,
+    animation_library: Res<AnimationLibrary>,
+    game_config: Res<GameConfig>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let Some(center) = ChunkCoord::at(player.x(), player.y()) else {
+        return;
+    };
+
+    let far: Vec<ChunkCoord> = chunks.loaded.iter().copied().filter(|chunk| chunk.distance(center) > CHUNK_UNLOAD_RADIUS).collect();
+    for chunk in far {
+        for (entity, member) in member_query.iter() {
+            if member.0 == chunk {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        chunks.loaded.remove(&chunk);
+        debug!(target: "zelda::world", "chunk ({}, {}) unloaded", chunk.x, chunk.y);
+    }
+
+    // Enemies only live in loaded chunks, those that walked out wait in the chunk they are in
+    for (entity, ennemy, brain) in ennemy_query.iter() {
+        let Some(chunk) = ChunkCoord::at(ennemy.x(), ennemy.y()) else {
+            continue;
+        };
+        if chunks.loaded.contains(&chunk) {
+            continue;
+        }
+        if !ennemy.is_dying() && !ennemy.is_dead() {
+            chunks.stored.entry(chunk).or_default().push(StoredEnnemy { ennemy: ennemy.clone(), guard: brain.is_shrine_guard() });
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for chunk in center.around(CHUNK_LOAD_RADIUS) {
+        if !chunks.loaded.insert(chunk) {
+            continue;
+        }
+        debug!(target: "zelda::world", "chunk ({}, {}) loaded", chunk.x, chunk.y);
+        spawn_chunk_tiles(&mut commands, &tile_map, &tile_assets, chunk);
+        for prop in plan.props.iter().filter(|prop| prop.chunk() == Some(chunk)) {
+            let entity = spawn_prop(&mut commands, &prop_atlases, prop);
+            commands.entity(entity).insert(ChunkMember(chunk));
+        }
+
+        if chunks.visited.insert(chunk) {
+            let ennemies = match &plan.ennemies {
+                Some(ennemies) => ennemies.iter().copied().filter(|&(x, y, _)| ChunkCoord::at(x, y) == Some(chunk)).collect(),
+                None => chunk_ennemies(&tile_map, game_config.seed, chunk),
+            };
+            for (x, y, guard) in ennemies {
+                let entity = spawn_ennemy(&mut commands, &animation_library, x, y);
+                if guard {
+                    commands.entity(entity).insert(Brain::shrine_guard());
+                }
+            }
+        }
+        for stored in chunks.stored.remove(&chunk).unwrap_or_default() {
+            let entity = spawn_ennemy_from(&mut commands, &animation_library, stored.ennemy);
+            if stored.guard {
+                commands.entity(entity).insert(Brain::shrine_guard());
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+
+use crate::export::ExportArgs;
+use crate::seed::parse_seed;
+
+pub struct CliArgs {
+    pub seed: Option<u64>,
+    pub replay: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub map: Option<PathBuf>,
+    pub export: Option<ExportArgs>,
+}
+
+impl CliArgs {
+    pub fn parse() -> Result<Self, String> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut cli = CliArgs { seed: None, replay: None, record: None, map: None, export: None };
+        // Export options can come before the export command
+        let mut export = false;
+        let mut out = None;
+        let mut png = None;
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--replay" => cli.replay = Some(path_value(&arg, args.next())?),
+                "--record" => cli.record = Some(path_value(&arg, args.next())?),
+                "--map" => cli.map = Some(path_value(&arg, args.next())?),
+                "export" => export = true,
+                "--out" => out = Some(path_value(&arg, args.next())?),
+                "--png" => png = Some(path_value(&arg, args.next())?),
+                // Otherwise a typo would silently become the seed
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+                _ if cli.seed.is_some() => return Err(format!("unexpected argument {}, there is already a seed", arg)),
+                _ => cli.seed = parse_seed(&arg),
+            }
+        }
+        if export {
+            cli.export = Some(ExportArgs { out, png });
+        } else if out.is_some() || png.is_some() {
+            return Err("--out and --png are only used by the export command".to_string());
+        }
+        Ok(cli)
+    }
+}
+
+fn path_value(option: &str, value: Option<String>) -> Result<PathBuf, String> {
+    match value {
+        Some(value) if !value.starts_with("--") => Ok(PathBuf::from(value)),
+        _ => Err(format!("{} needs a file", option)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn seed_and_options_are_read() {
+        let cli = parse(&["--out", "map.json", "export", "42"]).unwrap();
+        assert_eq!(cli.seed, Some(42));
+        assert_eq!(cli.export.and_then(|export| export.out), Some(PathBuf::from("map.json")));
+
+        let cli = parse(&["--map", "maps/island.ron"]).unwrap();
+        assert_eq!(cli.seed, None);
+        assert_eq!(cli.map, Some(PathBuf::from("maps/island.ron")));
+    }
+
+    #[test]
+    fn unknown_option_is_refused() {
+        assert!(parse(&["--sed", "42"]).err().unwrap().contains("--sed"));
+    }
+
+    #[test]
+    fn missing_value_is_refused() {
+        assert!(parse(&["--replay"]).err().unwrap().contains("--replay"));
+        assert!(parse(&["--record", "--map", "island.ron"]).err().unwrap().contains("--record"));
+    }
+
+    #[test]
+    fn out_without_export_is_refused() {
+        assert!(parse(&["--out", "map.json", "42"]).err().unwrap().contains("export"));
+        assert!(parse(&["42", "--png", "map.png"]).err().unwrap().contains("export"));
+    }
+
+    #[test]
+    fn extra_positional_is_refused() {
+        assert!(parse(&["42", "43"]).err().unwrap().contains("43"));
+        assert!(parse(&["export", "42", "map.json"]).err().unwrap().contains("map.json"));
+    }
+}
@@ -0,0 +1,264 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+use crate::GameConfig;
+use crate::GameState;
+use crate::animation::AnimationLibrary;
+use crate::constants::*;
+use crate::ennemies::*;
+use crate::entitypattern::EntityBehavior;
+use crate::player::Player;
+use crate::structures::{BossProgress, Sanctuary, Tower};
+use crate::tick::{SimPosition, latch_inputs};
+
+// Only compiled with `cargo run --features debug`
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_systems(Startup, spawn_console)
+            .add_systems(PreUpdate, console_input.after(InputSystem).before(latch_inputs))
+            .add_systems(Update, (run_console_commands, update_console_text).chain());
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConsoleCommand {
+    Seed,
+    Teleport(f32, f32),
+    SpawnSkeleton(usize),
+    Heal,
+    God,
+    RevealShrines,
+    KillAll,
+    SetState(GameState),
+    Noclip,
+}
+
+impl ConsoleCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["seed"] => Ok(ConsoleCommand::Seed),
+            ["teleport", x, y] => {
+                let x = x.parse().map_err(|_| format!("invalid x: {}", x))?;
+                let y = y.parse().map_err(|_| format!("invalid y: {}", y))?;
+                Ok(ConsoleCommand::Teleport(x, y))
+            },
+            ["spawn", "skeleton"] => Ok(ConsoleCommand::SpawnSkeleton(1)),
+            ["spawn", "skeleton", n] => n.parse()
+                .map(ConsoleCommand::SpawnSkeleton)
+                .map_err(|_| format!("invalid number: {}", n)),
+            ["heal"] => Ok(ConsoleCommand::Heal),
+            ["god"] => Ok(ConsoleCommand::God),
+            ["reveal_shrines"] => Ok(ConsoleCommand::RevealShrines),
+            ["kill_all"] => Ok(ConsoleCommand::KillAll),
+            ["set_state", state] => parse_state(state).map(ConsoleCommand::SetState),
+            ["noclip"] => Ok(ConsoleCommand::Noclip),
+            _ => Err(format!("unknown command: {}", line.trim())),
+        }
+    }
+}
+
+fn parse_state(name: &str) -> Result<GameState, String> {
+    match name {
+        "Menu" => Ok(GameState::Menu),
+        "Playing" => Ok(GameState::Playing),
+        "Paused" => Ok(GameState::Paused),
+        "GameOver" => Ok(GameState::GameOver),
+        "Victory" => Ok(GameState::Victory),
+        "Loading" => Ok(GameState::Loading),
+        "Settings" => Ok(GameState::Settings),
+        "Editor" => Ok(GameState::Editor),
+        _ => Err(format!("unknown state: {}", name)),
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Console {
+    open: bool,
+    input: String,
+    pending: Vec<String>,
+    history: Vec<String>,
+}
+
+impl Console {
+    fn log(&mut self, line: String) {
+        info!(target: "zelda::console", "{}", line);
+        self.history.push(line);
+        if self.history.len() > CONSOLE_HISTORY_LINES {
+            self.history.remove(0);
+        }
+    }
+}
+
+#[derive(Component)]
+struct ConsoleWindow;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn spawn_console(
+    mut commands: Commands,
+) {
+    commands
+        .spawn((NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(100),
+            ..default()
+        }, ConsoleWindow))
+        .with_children(|parent| {
+            parent.spawn((TextBundle::from_section("", TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..Default::default()
+            }), ConsoleText));
+        });
+}
+
+fn console_input(
+    mut console: ResMut<Console>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Grave) {
+        console.open = !console.open;
+    }
+    for event in characters.iter() {
+        if console.open && !event.char.is_control() && event.char != '`' && event.char != '²' {
+            console.input.push(event.char);
+        }
+    }
+    if !console.open {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        if !line.trim().is_empty() {
+            console.pending.push(line);
+        }
+    }
+    // The game must not react to what is typed in the console
+    keyboard_input.reset_all();
+}
+
+fn run_console_commands(
+    mut commands: Commands,
+    mut console: ResMut<Console>,
+    game_config: Res<GameConfig>,
+    mut player_query: Query<(&mut Player, &mut SimPosition)>,
+    mut ennemy_query: Query<&mut Ennemy>,
+    mut sanctuary_query: Query<&mut Sanctuary>,
+    mut tower_query: Query<&mut Tower>,
+    mut next_state: ResMut<NextState<GameState>>,
+    animation_library: Res<AnimationLibrary>,
+) {
+    for line in std::mem::take(&mut console.pending) {
+        console.log(format!("> {}", line));
+        let command = match ConsoleCommand::parse(&line) {
+            Ok(command) => command,
+            Err(e) => {
+                console.log(e);
+                continue;
+            },
+        };
+
+        if let ConsoleCommand::Seed = command {
+            console.log(format!("seed {}", game_config.seed));
+            continue;
+        }
+        if let ConsoleCommand::SetState(state) = command {
+            console.log(format!("state set to {:?}", state));
+            next_state.set(state);
+            continue;
+        }
+
+        let Ok((mut player, mut position)) = player_query.get_single_mut() else {
+            console.log("no game in progress".to_string());
+            continue;
+        };
+        match command {
+            ConsoleCommand::Teleport(x, y) => {
+                player.set_x(x);
+                player.set_y(y);
+                position.snap(x, y);
+                console.log(format!("teleported to {} {}", x, y));
+            },
+            ConsoleCommand::SpawnSkeleton(n) => {
+                for i in 0..n {
+                    let angle = i as f32 * std::f32::consts::TAU / n as f32;
+                    let x = player.x() + angle.cos() * CONSOLE_SPAWN_DISTANCE;
+                    let y = player.y() + angle.sin() * CONSOLE_SPAWN_DISTANCE;
+                    spawn_ennemy(&mut commands, &animation_library, x, y);
+                }
+                console.log(format!("{} skeleton(s) spawned", n));
+            },
+            ConsoleCommand::Heal => {
+                player.restore_health();
+                console.log("player healed".to_string());
+            },
+            ConsoleCommand::God => {
+                let enabled = player.toggle_god();
+                console.log(format!("god mode {}", if enabled { "on" } else { "off" }));
+            },
+            ConsoleCommand::Noclip => {
+                let enabled = player.toggle_noclip();
+                console.log(format!("noclip {}", if enabled { "on" } else { "off" }));
+            },
+            ConsoleCommand::RevealShrines => {
+                for mut sanctuary in sanctuary_query.iter_mut() {
+                    sanctuary.reveal();
+                }
+                for mut tower in tower_query.iter_mut() {
+                    if tower.boss_progress() == BossProgress::Dormant {
+                        tower.set_boss_progress(BossProgress::Summoned);
+                    }
+                }
+                console.log("all shrines revealed".to_string());
+            },
+            ConsoleCommand::KillAll => {
+                let mut killed = 0;
+                for mut ennemy in ennemy_query.iter_mut().filter(|ennemy| !ennemy.is_dying() && !ennemy.is_dead()) {
+                    ennemy.kill();
+                    killed += 1;
+                }
+                console.log(format!("{} ennemies killed", killed));
+            },
+            ConsoleCommand::Seed | ConsoleCommand::SetState(_) => (),
+        }
+    }
+}
+
+fn update_console_text(
+    console: Res<Console>,
+    mut window_query: Query<&mut Visibility, With<ConsoleWindow>>,
+    mut text_query: Query<&mut Text, With<ConsoleText>>,
+) {
+    for mut visibility in window_query.iter_mut() {
+        *visibility = if console.open { Visibility::Visible } else { Visibility::Hidden };
+    }
+    if !console.open {
+        return;
+    }
+    for mut text in text_query.iter_mut() {
+        let mut content = console.history.join("\n");
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        content.push_str(&format!("> {}_", console.input));
+        text.sections[0].value = content;
+    }
+}
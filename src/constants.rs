@@ -1,18 +1,42 @@
 pub const DEFAULT_SEED: u64 = 8000;
 
+// SIMULATION
+pub const FIXED_TIMESTEP: f32 = 1. / 60.;
+pub const TIMESTEP_EPSILON: f32 = 1e-4; // seconds, for durations that are whole ticks but add up to a bit less
+
 // WINDOW
 pub const WINDOW_WIDTH: f32 = 1920.;
 pub const WINDOW_HEIGHT: f32 = 1080.;
-pub const MAP_SIZE: f32 = 1400.;
+pub const VIEW_MAX_WIDTH: f32 = 2560.; // largest of RESOLUTIONS, bigger windows see the same area of the world
+pub const VIEW_MAX_HEIGHT: f32 = 1440.;
+pub const MAP_SIZE: f32 = WORLD_CHUNKS as f32 * CHUNK_SIZE;
 pub const CAMERA_DEFAULT_SCALE: f32 = 0.2;
-// pub const CAMERA_MIN_SCALE: f32 = 0.1;
-// pub const CAMERA_MAX_SCALE: f32 = 1.;
+pub const CAMERA_MIN_SCALE: f32 = 0.05;
+pub const CAMERA_MAX_SCALE: f32 = 2.;
+pub const CAMERA_ZOOM_LEVELS: [f32; 4] = [0.12, CAMERA_DEFAULT_SCALE, 0.3, 0.45];
+pub const CAMERA_DEFAULT_ZOOM_LEVEL: usize = 1;
+pub const CAMERA_DEAD_ZONE_WIDTH: f32 = 24.;
+pub const CAMERA_DEAD_ZONE_HEIGHT: f32 = 14.;
+pub const CAMERA_DAMPING: f32 = 6.; // fraction of the remaining distance covered per second, exponential
+pub const CAMERA_LOOK_AHEAD: f32 = 20.;
+pub const CAMERA_SHAKE_MAX_OFFSET: f32 = 6.;
+pub const CAMERA_SHAKE_MAX_ANGLE: f32 = 0.04;
+pub const CAMERA_TRAUMA_DECAY: f32 = 1.5; // per second
+pub const CAMERA_TRAUMA_PLAYER_HIT: f32 = 0.4;
+pub const CAMERA_TRAUMA_ENEMY_HIT: f32 = 0.15;
+pub const CAMERA_TRAUMA_BOSS_AREA: f32 = 0.7;
+#[cfg(feature = "debug")]
+pub const CONSOLE_HISTORY_LINES: usize = 10;
+#[cfg(feature = "debug")]
+pub const CONSOLE_SPAWN_DISTANCE: f32 = 40.;
 
 pub const Z_LAYER_BACKGROUND: f32 = 0.;
-pub const Z_LAYER_PLAYER: f32 = 1.;
+pub const Z_LAYER_TILES: f32 = 0.5;
+pub const Z_LAYER_DECALS: f32 = 0.8; // drawn on the ground, under every world sprite
+pub const Z_LAYER_WORLD: f32 = 1.;
+pub const Z_WORLD_DEPTH: f32 = 900.; // world sprites z from Z_LAYER_WORLD at the top of the map to this more at the bottom, the camera is at 999.9
+pub const Z_LAYER_OVERLAY: f32 = Z_LAYER_WORLD + Z_WORLD_DEPTH + 1.;
 pub const Z_LAYER_GUI: f32 = f32::MAX;
-pub const Z_LAYER_STRUCTURES: f32 = 2.;
-pub const Z_LAYER_ENNEMIES: f32 = Z_LAYER_PLAYER;
 
 
 // PLAYER
@@ -20,8 +44,8 @@ pub const PLAYER_SPRITE_SIZE: f32 = 32.;
 pub const PLAYER_SPRITE_SCALE: f32 = 0.5;
 pub const PLAYER_HITBOX_WIDTH: f32 = PLAYER_SPRITE_SIZE * PLAYER_SPRITE_SCALE;
 pub const PLAYER_HITBOX_HEIGHT: f32 = PLAYER_SPRITE_SIZE * PLAYER_SPRITE_SCALE;
-pub const PLAYER_NORMAL_SPEED: f32 = 1.;
-pub const PLAYER_SPRINT_SPEED: f32 = 2.;
+pub const PLAYER_NORMAL_SPEED: f32 = 60.;
+pub const PLAYER_SPRINT_SPEED: f32 = 120.;
 pub const PLAYER_DAMAGE: i32 = 2;
 pub const PLAYER_ATTACK_RANGE: f32 = 5.;
 pub const PLAYER_ATTACK_DELAY: u64 = 1000;
@@ -34,31 +58,131 @@ pub const TOWER_WIDTH: f32 = 67.;
 pub const SANCTUARY_HEIGHT: f32 = 75.;
 pub const SANCTUARY_WIDTH: f32 = 96.;
 pub const SANCTUARY_HEALING: i32 = 5;
+pub const SANCTUARY_MAX_ATTEMPTS: usize = 10; // random tries before looking at every tile
+
+// TILEMAP
+pub const TILE_SIZE: f32 = 20.;
+pub const MAP_TILES: usize = (MAP_SIZE / TILE_SIZE) as usize;
+pub const RIVER_WIDTH: i32 = 3; // tiles
+pub const ROAD_WIDTH: i32 = 3;
+pub const ROAD_WANDER: i32 = 24; // how far the road goes from the middle row
+pub const SPAWN_CLEAR_RADIUS: i32 = 3;
+pub const CLIFF_RIDGES: i32 = 40;
+pub const CLIFF_RIDGE_MIN_LENGTH: i32 = 5;
+pub const CLIFF_RIDGE_MAX_LENGTH: i32 = 12;
+
+// WORLD GENERATION
+pub const BIOME_NOISE_SCALE: f32 = 0.08; // per tile, smaller means larger biomes
+pub const BIOME_CLEARING_THRESHOLD: f32 = 0.4;
+pub const BIOME_GRAVEYARD_THRESHOLD: f32 = 0.62;
+pub const PATH_WIDTH: i32 = 3; // tiles
+pub const PATH_WANDER: f64 = 0.3; // chance to step along the shortest axis
+pub const TREE_CANDIDATES_PER_CHUNK: usize = 10; // how many are kept depends on the biome
+pub const BUSH_CANDIDATES_PER_CHUNK: usize = 8;
+pub const GRAVE_CANDIDATES_PER_CHUNK: usize = 4;
+pub const PROP_VARIANTS: usize = 3; // sprites of each tree, bush and grave
+
+// CHUNKS
+pub const CHUNK_TILES: usize = 16;
+pub const CHUNK_SIZE: f32 = CHUNK_TILES as f32 * TILE_SIZE;
+pub const WORLD_CHUNKS: usize = 16; // 5120 pixels wide, 13 times the area of the first 1400 pixels map
+pub const CHUNK_LOAD_RADIUS: usize = 2; // in chunks around the player
+pub const CHUNK_UNLOAD_RADIUS: usize = 3;
+pub const BACKGROUND_IMAGE_SIZE: f32 = 1400.;
 
 // ENNEMIES
-pub const ENNEMIES_NUMBER: i32 = 60;
+pub const ENNEMIES_PER_CHUNK: i32 = 4; // before the ones that would spawn in a wall or next to the player
+pub const ENNEMY_HEALTH: i32 = 10;
+pub const ENNEMY_ATTACK: i32 = 5;
 pub const ENNEMY_SPRITE_SIZE: f32 = 32.;
 pub const ENNEMY_SPRITE_SCALE: f32 = 0.5;
 pub const ENNEMY_HITBOX_WIDTH: f32 = ENNEMY_SPRITE_SIZE * ENNEMY_SPRITE_SCALE;
 pub const ENNEMY_HITBOX_HEIGHT: f32 = ENNEMY_SPRITE_SIZE * ENNEMY_SPRITE_SCALE;
-pub const ENNEMY_SPRINT_SPEED: f32 = 1.3;
-pub const ENNEMY_NORMAL_SPEED: f32 = 0.6;
-pub const ENNEMY_ATTACK_SPEED: f32 = 0.4;
+pub const ENNEMY_SPRINT_SPEED: f32 = 78.;
+pub const ENNEMY_NORMAL_SPEED: f32 = 36.;
 pub const ENNEMY_ATTACK_RANGE: f32 = 5.;
 pub const ENNEMY_AGGRO_DISTANCE: f32 = 120.;
 pub const ENNEMY_DEFENCE_RATIO: f32 = 0.2;
+pub const ENNEMY_GUARD_RATIO: i32 = 5;
+
+// ENNEMY AI
+pub const AI_CURRENT_NODE_BONUS: f32 = 0.05;
+pub const AI_INVESTIGATE_REACHED_DISTANCE: f32 = 8.;
+pub const AI_FLANK_MIN_ALLIES: usize = 2;
+pub const AI_FLANK_MIN_DISTANCE: f32 = 40.;
+pub const AI_FLANK_OFFSET: f32 = 30.;
+pub const AI_RETREAT_HEALTH_RATIO: f32 = 0.3;
+pub const AI_GUARD_RADIUS: f32 = 80.;
+
+// SPAWN DIRECTOR
+pub const DIRECTOR_RADIUS: f32 = 700.; // reaches past the widest view, enemies spawn out of sight
+pub const DIRECTOR_BASE_DENSITY: usize = 6;
+pub const DIRECTOR_DENSITY_PER_SHRINE: usize = 1;
+pub const DIRECTOR_DENSITY_MARGIN: usize = 4;
+pub const DIRECTOR_COOLDOWN: f32 = 4.;
+pub const DIRECTOR_COOLDOWN_REDUCTION_PER_SHRINE: f32 = 0.15;
+pub const DIRECTOR_HEALTH_PER_SHRINE: i32 = 1;
+pub const DIRECTOR_SPAWN_ATTEMPTS: i32 = 10;
+
+// BOSS
+pub const BOSS_HEALTH: i32 = 60;
+pub const BOSS_ATTACK: i32 = 4;
+pub const BOSS_DEFENCE_RATIO: f32 = 0.1;
+pub const BOSS_SPRITE_SCALE: f32 = ENNEMY_SPRITE_SCALE * 2.;
+pub const BOSS_HITBOX_WIDTH: f32 = ENNEMY_HITBOX_WIDTH * 2.;
+pub const BOSS_HITBOX_HEIGHT: f32 = ENNEMY_HITBOX_HEIGHT * 2.;
+pub const BOSS_CHARGE_SPEED: f32 = 180.;
+pub const BOSS_CHARGE_DURATION: f32 = 0.8;
+pub const BOSS_CHARGE_COOLDOWN: f32 = 3.;
+pub const BOSS_SUMMON_NUMBER: i32 = 3;
+pub const BOSS_SUMMON_DISTANCE: f32 = 30.;
+pub const BOSS_SUMMON_COOLDOWN: f32 = 8.;
+pub const BOSS_AREA_RADIUS: f32 = 50.;
+pub const BOSS_AREA_DAMAGE: i32 = 6;
+pub const BOSS_AREA_WINDUP: f32 = 1.2;
+pub const BOSS_AREA_COOLDOWN: f32 = 5.;
 
 // OTHERS
 pub const TREE_HEIGHT: f32 = 160.;
 pub const TREE_WIDTH: f32 = 128.;
 pub const TREE_TRANSPARENCY: f32 = 0.6;
-pub const TREE_NUMBER: i32 = 100;
 pub const BUSH_HEIGHT: f32 = 50.;
 pub const BUSH_WIDTH: f32 = 55.;
 pub const BUSH_TRANSPARENCY: f32 = 0.6;
-pub const BUSH_NUMBER: i32 = 100;
-pub const GRAVES_NUMBER: i32 = 25;
+pub const FOOTSTEP_DUST_ALPHA: f32 = 0.5;
+pub const FOOTSTEP_DUST_WIDTH: f32 = 5.;
+pub const FOOTSTEP_DUST_HEIGHT: f32 = 2.;
+pub const FOOTSTEP_DUST_DURATION: f32 = 0.4;
+
+
+// AUDIO
+pub const AUDIO_DEFAULT_MASTER_VOLUME: f32 = 0.8;
+pub const AUDIO_DEFAULT_MUSIC_VOLUME: f32 = 0.6;
+pub const AUDIO_DEFAULT_SFX_VOLUME: f32 = 0.8;
+pub const AUDIO_CROSSFADE_DURATION: f32 = 1.5;
+pub const AUDIO_COMBAT_LINGER: f32 = 3.;
+
+// MENU
+pub const SEED_INPUT_MAX_LENGTH: usize = 32;
+
+// EDITOR
+pub const EDITOR_GRID_SIZE: f32 = TILE_SIZE;
+pub const EDITOR_CAMERA_SCALE: f32 = 1.;
+pub const EDITOR_CAMERA_SPEED: f32 = 600.; // pixels per second at scale 1
+pub const EDITOR_BOX_STEP: f32 = 2.;
+pub const Z_EDITOR_MARKERS: f32 = Z_LAYER_OVERLAY; // enemy spawns stay visible over the trees
+
+// LOADING
+pub const LOADING_BAR_WIDTH: f32 = 400.;
+pub const LOADING_BAR_HEIGHT: f32 = 20.;
+
+// EXPORT
+pub const EXPORT_PNG_SCALE: f32 = 4.; // world pixels per pixel of the rendered layout
 
+// SETTINGS
+pub const RESOLUTIONS: [(u32, u32); 4] = [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440)];
+pub const DIFFICULTY_EASY_MULTIPLIER: f32 = 0.75;
+pub const DIFFICULTY_HARD_MULTIPLIER: f32 = 1.5;
 
 // RNG OFFSETS
 pub const OFFSET_TREE: u64 = 0;
@@ -67,5 +191,11 @@ pub const OFFSET_GRAVE: u64 = 2000;
 pub const OFFSET_ENNEMY: u64 = 3000;
 pub const OFFSET_SANCTUARY: u64 = 4000;
 pub const OFFSET_TOWER: u64 = 5000;
+pub const OFFSET_DIRECTOR: u64 = 6000;
+pub const OFFSET_RNG_AI: u64 = 7000;
+pub const OFFSET_RNG_COMBAT: u64 = 8000;
+pub const OFFSET_TILEMAP: u64 = 9000;
+pub const OFFSET_BIOME: u64 = 10000;
+pub const OFFSET_PATHS: u64 = 11000;
 
 
@@ -0,0 +1,200 @@
+use bevy::diagnostic::{DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin};
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+
+use crate::GameState;
+use crate::ai::Brain;
+use crate::boss::Boss;
+use crate::camera::CameraController;
+use crate::collisions::*;
+use crate::constants::*;
+use crate::ennemies::Ennemy;
+use crate::entitypattern::EntityBehavior;
+use crate::player::Player;
+
+// Only compiled with `cargo run --features debug`
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((FrameTimeDiagnosticsPlugin, EntityCountDiagnosticsPlugin))
+            .init_resource::<DebugSettings>()
+            // Spawned again if a screen change ever sweeps it away
+            .add_systems(Update, spawn_stats_text.run_if(not(any_with_component::<StatsText>())))
+            .add_systems(Update, (toggle_debug_settings,
+                                  draw_collisions.run_if(|settings: Res<DebugSettings>| settings.collisions),
+                                  draw_ranges.run_if(|settings: Res<DebugSettings>| settings.ranges),
+                                  update_state_labels,
+                                  update_stats_text,
+                                  ))
+            .add_systems(Update, free_zoom.run_if(in_state(GameState::Playing)));
+    }
+}
+
+#[derive(Resource)]
+pub struct DebugSettings {
+    collisions: bool,
+    ranges: bool,
+    states: bool,
+    stats: bool,
+}
+
+impl Default for DebugSettings {
+    fn default() -> Self {
+        DebugSettings { collisions: false, ranges: false, states: false, stats: true }
+    }
+}
+
+#[derive(Component)]
+struct StateLabel {
+    target: Entity,
+}
+
+#[derive(Component)]
+struct StatsText;
+
+fn toggle_debug_settings(
+    mut settings: ResMut<DebugSettings>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F1) {
+        settings.collisions = !settings.collisions;
+    }
+    if keyboard_input.just_pressed(KeyCode::F2) {
+        settings.ranges = !settings.ranges;
+    }
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        settings.states = !settings.states;
+    }
+    if keyboard_input.just_pressed(KeyCode::F4) {
+        settings.stats = !settings.stats;
+    }
+}
+
+fn draw_collisions(
+    mut gizmos: Gizmos,
+    collision_query: Query<&CollisionComponent>,
+) {
+    for collision in collision_query.iter() {
+        let (x, y, w, h) = collision.get_hitbox();
+        gizmos.rect_2d(Vec2::new(x, y), 0., Vec2::new(w, h), Color::RED);
+    }
+}
+
+fn draw_ranges(
+    mut gizmos: Gizmos,
+    ennemy_query: Query<(&Ennemy, Option<&Boss>)>,
+    player_query: Query<&Player>,
+) {
+    for (ennemy, boss) in ennemy_query.iter() {
+        let (x, y, w, h) = ennemy.get_hitbox();
+        let position = Vec2::new(x, y);
+        gizmos.circle_2d(position, ENNEMY_AGGRO_DISTANCE, Color::YELLOW);
+        gizmos.rect_2d(position, 0., Vec2::new(w, h) + 2. * ENNEMY_ATTACK_RANGE, Color::ORANGE);
+        if boss.is_some() {
+            gizmos.circle_2d(position, BOSS_AREA_RADIUS, Color::PURPLE);
+        }
+    }
+    for player in player_query.iter() {
+        let (x, y, w, h) = player.get_hitbox();
+        gizmos.rect_2d(Vec2::new(x, y), 0., Vec2::new(w, h) + 2. * PLAYER_ATTACK_RANGE, Color::CYAN);
+    }
+}
+
+fn update_state_labels(
+    mut commands: Commands,
+    settings: Res<DebugSettings>,
+    ennemy_query: Query<(Entity, &Ennemy, Option<&Brain>)>,
+    mut label_query: Query<(Entity, &StateLabel, &mut Text, &mut Transform)>,
+) {
+    for (label_entity, label, mut text, mut transform) in label_query.iter_mut() {
+        let Ok((_, ennemy, brain)) = ennemy_query.get(label.target) else {
+            commands.entity(label_entity).despawn();
+            continue;
+        };
+        if !settings.states {
+            commands.entity(label_entity).despawn();
+            continue;
+        }
+        let (x, y, _, h) = ennemy.get_hitbox();
+        transform.translation = Vec3::new(x, y + h / 2. + 4., Z_LAYER_OVERLAY);
+        text.sections[0].value = match brain.and_then(|brain| brain.current()) {
+            Some(node) => format!("{:?} ({})", ennemy.state(), node),
+            None => format!("{:?}", ennemy.state()),
+        };
+    }
+
+    if !settings.states {
+        return;
+    }
+    for (entity, ennemy, _) in ennemy_query.iter() {
+        if label_query.iter().any(|(_, label, _, _)| label.target == entity) {
+            continue;
+        }
+        commands.spawn((Text2dBundle {
+            text: Text::from_section(format!("{:?}", ennemy.state()), TextStyle {
+                font_size: 30.0,
+                color: Color::WHITE,
+                ..Default::default()
+            }),
+            transform: Transform {
+                translation: Vec3::new(ennemy.x(), ennemy.y(), Z_LAYER_OVERLAY),
+                scale: Vec3::splat(0.2),
+                ..Default::default()
+            },
+            ..Default::default()
+        }, StateLabel { target: entity }));
+    }
+}
+
+fn spawn_stats_text(
+    mut commands: Commands,
+) {
+    commands.spawn((TextBundle {
+        text: Text::from_section("", TextStyle {
+            font_size: 20.0,
+            color: Color::WHITE,
+            ..Default::default()
+        }),
+        style: Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        ..Default::default()
+    }, StatsText));
+}
+
+fn update_stats_text(
+    settings: Res<DebugSettings>,
+    diagnostics: Res<DiagnosticsStore>,
+    ennemy_query: Query<(), With<Ennemy>>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<StatsText>>,
+) {
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        *visibility = if settings.stats { Visibility::Visible } else { Visibility::Hidden };
+        let fps = diagnostics.get(FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.smoothed())
+            .unwrap_or(0.);
+        let entities = diagnostics.get(EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+            .and_then(|count| count.value())
+            .unwrap_or(0.);
+        text.sections[0].value = format!("FPS: {:.0}  Entities: {:.0}  Ennemies: {}", fps, entities, ennemy_query.iter().count());
+    }
+}
+
+fn free_zoom(
+    mut controller_query: Query<&mut CameraController>,
+    mut wheel_events: EventReader<MouseWheel>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    let mut controller = controller_query.single_mut();
+    for event in wheel_events.iter() {
+        let zoom = controller.zoom() * (1. - event.y * 0.1);
+        controller.set_zoom(zoom);
+    }
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        controller.reset_zoom();
+    }
+}
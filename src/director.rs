@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::GameState;
+use crate::animation::AnimationLibrary;
+use crate::boss::Boss;
+use crate::chunks::{ChunkCoord, LoadedChunks};
+use crate::collisions::*;
+use crate::constants::*;
+use crate::ennemies::*;
+use crate::entitypattern::EntityBehavior;
+use crate::player::Player;
+use crate::rng::GameRng;
+use crate::structures::Sanctuary;
+use crate::tilemap::TileMap;
+use crate::worldgen::WorldPlan;
+use crate::tick::*;
+
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnTransition { from: GameState::Loading, to: GameState::Playing }, setup_director)
+            .add_systems(FixedUpdate, direct_spawns.in_set(TickSet::Director).run_if(in_state(GameState::Playing)));
+    }
+}
+
+#[derive(Resource)]
+pub struct SpawnDirector {
+    cooldown: Timer,
+}
+
+impl Default for SpawnDirector {
+    fn default() -> Self {
+        SpawnDirector::new()
+    }
+}
+
+impl SpawnDirector {
+    pub fn new() -> Self {
+        SpawnDirector {
+            cooldown: Timer::from_seconds(DIRECTOR_COOLDOWN, TimerMode::Once),
+        }
+    }
+
+    fn target_density(unlocked: usize) -> (usize, usize) {
+        let min = DIRECTOR_BASE_DENSITY + unlocked * DIRECTOR_DENSITY_PER_SHRINE;
+        (min, min + DIRECTOR_DENSITY_MARGIN)
+    }
+
+    fn cooldown_for(unlocked: usize) -> f32 {
+        DIRECTOR_COOLDOWN / (1. + unlocked as f32 * DIRECTOR_COOLDOWN_REDUCTION_PER_SHRINE)
+    }
+
+    // Widest view the camera can show, at the largest resolution (the camera never shows more), with the dead zone and look-ahead it can lag or lead the player by.
+    // It does not depend on the real window so that a run only depends on the simulation
+    fn view_half_size() -> (f32, f32) {
+        let widest = CAMERA_ZOOM_LEVELS.iter().copied().fold(CAMERA_DEFAULT_SCALE, f32::max);
+        (widest * VIEW_MAX_WIDTH / 2. + CAMERA_DEAD_ZONE_WIDTH + CAMERA_LOOK_AHEAD,
+         widest * VIEW_MAX_HEIGHT / 2. + CAMERA_DEAD_ZONE_HEIGHT + CAMERA_LOOK_AHEAD)
+    }
+
+    // Where the camera looks at most when following the player, kept inside the map like the camera
+    fn view_center(player_pos: (f32, f32)) -> (f32, f32) {
+        let (half_width, half_height) = SpawnDirector::view_half_size();
+        let limit_x = (MAP_SIZE / 2. - half_width).max(0.);
+        let limit_y = (MAP_SIZE / 2. - half_height).max(0.);
+        (player_pos.0.clamp(-limit_x, limit_x), player_pos.1.clamp(-limit_y, limit_y))
+    }
+
+    fn find_spawn_position(
+        rng: &mut StdRng,
+        player_pos: (f32, f32),
+        tile_map: &TileMap,
+        plan: &WorldPlan,
+        chunks: &LoadedChunks,
+        collision_query: &Query<&CollisionComponent>,
+    ) -> Option<(f32, f32)> {
+        let (half_width, half_height) = SpawnDirector::view_half_size();
+        let view_pos = SpawnDirector::view_center(player_pos);
+        // Only the view rectangle is excluded, the ring of its diagonal would reach out of the loaded chunks
+        let min_distance = ENNEMY_AGGRO_DISTANCE;
+        let max_value = MAP_SIZE / 2. - ENNEMY_HITBOX_WIDTH;
+
+        for _ in 0..DIRECTOR_SPAWN_ATTEMPTS {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let distance = rng.gen_range(min_distance..DIRECTOR_RADIUS);
+            let x = player_pos.0 + angle.cos() * distance;
+            let y = player_pos.1 + angle.sin() * distance;
+
+            if x.abs() > max_value || y.abs() > max_value {
+                continue;
+            }
+            if (x - view_pos.0).abs() < half_width + ENNEMY_HITBOX_WIDTH && (y - view_pos.1).abs() < half_height + ENNEMY_HITBOX_HEIGHT {
+                continue;
+            }
+            // Props and terrain only collide in loaded chunks, an enemy out of them would also go straight to storage
+            if !ChunkCoord::at(x, y).is_some_and(|chunk| chunks.is_loaded(chunk)) {
+                continue;
+            }
+            if !tile_map.is_area_free(x, y, ENNEMY_HITBOX_WIDTH, ENNEMY_HITBOX_HEIGHT) {
+                continue;
+            }
+            let hitbox = CollisionComponent::new(x, y, ENNEMY_HITBOX_WIDTH, ENNEMY_HITBOX_HEIGHT);
+            // The props of a chunk loaded during this tick have no collision entity yet
+            if plan.props.iter().filter_map(|prop| prop.hitbox()).any(|prop_hitbox| hitbox.would_collide_with(&prop_hitbox)) {
+                continue;
+            }
+            if collision_query.iter().any(|collisionable| hitbox.would_collide_with(collisionable)) {
+                continue;
+            }
+            return Some((x, y));
+        }
+        None
+    }
+}
+
+fn setup_director(
+    mut commands: Commands,
+) {
+    commands.insert_resource(SpawnDirector::new());
+}
+
+pub fn direct_spawns(
+    mut commands: Commands,
+    mut director: ResMut<SpawnDirector>,
+    mut game_rng: ResMut<GameRng>,
+    ennemy_query: Query<(Entity, &Ennemy), Without<Boss>>,
+    player_query: Query<&Player>,
+    sanctuary_query: Query<&Sanctuary>,
+    collision_query: Query<&CollisionComponent>,
+    tile_map: Res<TileMap>,
+    plan: Res<WorldPlan>,
+    chunks: Res<LoadedChunks>,
+    animation_library: Res<AnimationLibrary>,
+) {
+    director.cooldown.tick(tick_duration());
+    if !director.cooldown.finished() {
+        return;
+    }
+
+    let player = player_query.single();
+    let player_pos = player.get_pos();
+
+    let unlocked = sanctuary_query.iter().filter(|sanctuary| sanctuary.is_unlocked()).count();
+    let (min_density, max_density) = SpawnDirector::target_density(unlocked);
+
+    let distance_to_player = |ennemy: &Ennemy| {
+        ((ennemy.x() - player_pos.0).powi(2) + (ennemy.y() - player_pos.1).powi(2)).sqrt()
+    };
+    let nearby: Vec<(Entity, &Ennemy)> = ennemy_query.iter()
+        .filter(|(_, ennemy)| !ennemy.is_dying() && !ennemy.is_dead())
+        .filter(|(_, ennemy)| distance_to_player(*ennemy) < DIRECTOR_RADIUS)
+        .collect();
+
+    if nearby.len() < min_density {
+        if let Some((x, y)) = SpawnDirector::find_spawn_position(&mut game_rng.director, player_pos, &tile_map, &plan, &chunks, &collision_query) {
+            let health = ENNEMY_HEALTH + unlocked as i32 * DIRECTOR_HEALTH_PER_SHRINE;
+            spawn_ennemy_with_stats(&mut commands, &animation_library, x, y, health, ENNEMY_ATTACK);
+        }
+    } else if nearby.len() > max_density {
+        let (half_width, half_height) = SpawnDirector::view_half_size();
+        let view_pos = SpawnDirector::view_center(player_pos);
+        let farthest = nearby.iter()
+            .filter(|(_, ennemy)| !ennemy.is_chasing())
+            .filter(|(_, ennemy)| (ennemy.x() - view_pos.0).abs() > half_width + ENNEMY_HITBOX_WIDTH || (ennemy.y() - view_pos.1).abs() > half_height + ENNEMY_HITBOX_HEIGHT)
+            .max_by(|(_, a), (_, b)| distance_to_player(*a).total_cmp(&distance_to_player(*b)));
+        if let Some((entity, _)) = farthest {
+            commands.entity(*entity).despawn();
+        }
+    }
+
+    let cooldown = SpawnDirector::cooldown_for(unlocked);
+    director.cooldown = Timer::from_seconds(cooldown, TimerMode::Once);
+}
@@ -0,0 +1,688 @@
+use std::path::Path;
+
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::GameConfig;
+use crate::GameState;
+use crate::animation::AnimationLibrary;
+use crate::chunks::ChunkCoord;
+use crate::collisions::*;
+use crate::constants::*;
+use crate::ennemies::chunk_ennemies;
+use crate::mapfile::{MapFile, load_map};
+use crate::menu::SeedInput;
+use crate::setup::{PropAtlases, spawn_prop};
+use crate::tilemap::{TileAssets, TileLayer, spawn_chunk_tiles};
+use crate::worldgen::{CustomWorld, PlannedProp, PropKind, WorldPlan, plan_world, terrain_for_plan};
+use crate::ysort::YSort;
+
+pub const EDITOR_MAP_PATH: &str = "maps/editor.ron";
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorAssets>()
+            .add_systems(OnEnter(GameState::Editor), open_editor)
+            .add_systems(OnExit(GameState::Editor), close_editor)
+            .add_systems(Update, (choose_tool,
+                                  move_editor_camera,
+                                  edit_with_mouse,
+                                  edit_collision_box,
+                                  save_or_leave_editor,
+                                  spawn_editor_terrain,
+                                  spawn_editor_objects,
+                                  draw_editor_boxes,
+                                  update_editor_text).chain().run_if(in_state(GameState::Editor).and_then(resource_exists::<Editor>())));
+    }
+}
+
+// The seed gives the terrain, everything on it is placed by hand
+struct EditorMap {
+    seed: u64,
+    plan: WorldPlan,
+}
+
+impl EditorMap {
+    // Starts from the world of the seed, with the enemies every chunk would draw
+    fn from_seed(seed: u64) -> Self {
+        let (tile_map, mut plan) = plan_world(seed);
+        plan.ennemies = Some(ChunkCoord::all().flat_map(|chunk| chunk_ennemies(&tile_map, seed, chunk)).collect());
+        EditorMap { seed, plan }
+    }
+
+    fn load(path: &Path) -> Result<Self, String> {
+        let (seed, plan) = load_map(path)?;
+        Ok(EditorMap { seed, plan })
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        MapFile::from_world(self.seed, &self.plan).save(path)
+    }
+
+    fn ennemies_mut(&mut self) -> &mut Vec<(f32, f32, bool)> {
+        self.plan.ennemies.get_or_insert_with(Vec::new)
+    }
+
+    // Area that can be clicked for each object, the whole sprite
+    fn objects(&self) -> Vec<(Selection, (f32, f32, f32, f32))> {
+        let mut objects = vec![(Selection::Tower, (self.plan.tower.0, self.plan.tower.1, TOWER_WIDTH, TOWER_HEIGHT))];
+        for (index, &(x, y)) in self.plan.sanctuaries.iter().enumerate() {
+            objects.push((Selection::Shrine(index), (x, y, SANCTUARY_WIDTH, SANCTUARY_HEIGHT)));
+        }
+        for (index, prop) in self.plan.props.iter().enumerate() {
+            let (w, h) = match prop.kind {
+                PropKind::Tree => (TREE_WIDTH, TREE_HEIGHT),
+                PropKind::Bush => (BUSH_WIDTH, BUSH_HEIGHT),
+                PropKind::Grave => (64., 64.),
+            };
+            objects.push((Selection::Prop(index), (prop.x, prop.y, w, h)));
+        }
+        for (index, &(x, y, _)) in self.plan.ennemies.iter().flatten().enumerate() {
+            objects.push((Selection::Ennemy(index), (x, y, ENNEMY_HITBOX_WIDTH, ENNEMY_HITBOX_HEIGHT)));
+        }
+        objects
+    }
+
+    // The smallest object under the point, so that a grave stays reachable under a tree
+    fn object_at(&self, x: f32, y: f32) -> Option<Selection> {
+        self.objects().into_iter()
+            .filter(|(_, (ox, oy, w, h))| (x - ox).abs() <= w / 2. && (y - oy).abs() <= h / 2.)
+            .min_by(|(_, (_, _, w1, h1)), (_, (_, _, w2, h2))| (w1 * h1).total_cmp(&(w2 * h2)))
+            .map(|(selection, _)| selection)
+    }
+
+    fn position(&self, selection: Selection) -> (f32, f32) {
+        match selection {
+            Selection::Tower => self.plan.tower,
+            Selection::Shrine(index) => self.plan.sanctuaries[index],
+            Selection::Prop(index) => (self.plan.props[index].x, self.plan.props[index].y),
+            Selection::Ennemy(index) => {
+                let (x, y, _) = self.plan.ennemies.as_ref().unwrap()[index];
+                (x, y)
+            },
+        }
+    }
+
+    fn move_to(&mut self, selection: Selection, x: f32, y: f32) {
+        match selection {
+            Selection::Tower => self.plan.tower = (x, y),
+            Selection::Shrine(index) => self.plan.sanctuaries[index] = (x, y),
+            Selection::Prop(index) => {
+                self.plan.props[index].x = x;
+                self.plan.props[index].y = y;
+            },
+            Selection::Ennemy(index) => {
+                let ennemy = &mut self.ennemies_mut()[index];
+                ennemy.0 = x;
+                ennemy.1 = y;
+            },
+        }
+    }
+
+    fn place(&mut self, tool: EditorTool, variant: usize, x: f32, y: f32) -> Selection {
+        let prop = |kind| PlannedProp { kind, x, y, index: variant, collision: None };
+        match tool {
+            // There is only one tower, placing it moves it
+            EditorTool::Tower => {
+                self.plan.tower = (x, y);
+                Selection::Tower
+            },
+            EditorTool::Shrine => {
+                self.plan.sanctuaries.push((x, y));
+                Selection::Shrine(self.plan.sanctuaries.len() - 1)
+            },
+            EditorTool::Tree | EditorTool::Bush | EditorTool::Grave => {
+                self.plan.props.push(prop(tool.prop_kind().unwrap()));
+                Selection::Prop(self.plan.props.len() - 1)
+            },
+            EditorTool::Ennemy => {
+                let ennemies = self.ennemies_mut();
+                ennemies.push((x, y, variant == 1));
+                Selection::Ennemy(ennemies.len() - 1)
+            },
+        }
+    }
+
+    fn remove(&mut self, selection: Selection) -> bool {
+        match selection {
+            Selection::Tower => return false,
+            Selection::Shrine(index) => {
+                self.plan.sanctuaries.remove(index);
+            },
+            Selection::Prop(index) => {
+                self.plan.props.remove(index);
+            },
+            Selection::Ennemy(index) => {
+                self.ennemies_mut().remove(index);
+            },
+        }
+        true
+    }
+
+    // Whether the selection has variants at all
+    fn next_variant(&mut self, selection: Selection) -> bool {
+        match selection {
+            Selection::Prop(index) => self.plan.props[index].index = (self.plan.props[index].index + 1) % PROP_VARIANTS,
+            Selection::Ennemy(index) => self.ennemies_mut()[index].2 ^= true,
+            Selection::Tower | Selection::Shrine(_) => return false,
+        }
+        true
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum EditorTool {
+    Tree,
+    Bush,
+    Grave,
+    Tower,
+    Shrine,
+    Ennemy,
+}
+
+impl EditorTool {
+    const ALL: [EditorTool; 6] = [EditorTool::Tree, EditorTool::Bush, EditorTool::Grave, EditorTool::Tower, EditorTool::Shrine, EditorTool::Ennemy];
+    const KEYS: [KeyCode; 6] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6];
+
+    // Sprite of props, skeleton or shrine guard for enemies
+    fn variants(&self) -> usize {
+        match self {
+            EditorTool::Tree | EditorTool::Bush | EditorTool::Grave => PROP_VARIANTS,
+            EditorTool::Tower | EditorTool::Shrine => 1,
+            EditorTool::Ennemy => 2,
+        }
+    }
+
+    fn prop_kind(&self) -> Option<PropKind> {
+        match self {
+            EditorTool::Tree => Some(PropKind::Tree),
+            EditorTool::Bush => Some(PropKind::Bush),
+            EditorTool::Grave => Some(PropKind::Grave),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Selection {
+    Tower,
+    Shrine(usize),
+    Prop(usize),
+    Ennemy(usize),
+}
+
+impl Selection {
+    fn is_structure(&self) -> bool {
+        matches!(self, Selection::Tower | Selection::Shrine(_))
+    }
+}
+
+#[derive(Resource)]
+struct Editor {
+    map: EditorMap,
+    tool: EditorTool,
+    variant: usize,
+    snap: bool,
+    selected: Option<Selection>,
+    // From the cursor to the dragged object
+    drag_offset: Option<Vec2>,
+    terrain_changed: bool,
+    objects_changed: bool,
+    saved: bool,
+    // Escape was pressed once with unsaved changes
+    leaving: bool,
+    message: String,
+}
+
+impl Editor {
+    fn new(map: EditorMap, message: String) -> Self {
+        Editor {
+            map,
+            tool: EditorTool::Tree,
+            variant: 0,
+            snap: true,
+            selected: None,
+            drag_offset: None,
+            terrain_changed: true,
+            objects_changed: true,
+            saved: true,
+            leaving: false,
+            message,
+        }
+    }
+
+    fn snap(&self, position: Vec2) -> Vec2 {
+        if self.snap {
+            (position / EDITOR_GRID_SIZE).round() * EDITOR_GRID_SIZE
+        } else {
+            position
+        }
+    }
+
+    fn changed(&mut self, selection: Selection) {
+        self.objects_changed = true;
+        self.terrain_changed |= selection.is_structure();
+        self.saved = false;
+        self.leaving = false;
+    }
+}
+
+#[derive(Resource)]
+struct EditorAssets {
+    tower: Handle<Image>,
+    sanctuary: Handle<TextureAtlas>,
+    skeleton: Handle<TextureAtlas>,
+}
+
+impl FromWorld for EditorAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        let tower = asset_server.load("tower.png");
+        let sanctuary_handle = asset_server.load("sanctuary.png");
+        let skeleton = world.resource::<AnimationLibrary>().atlas("skeleton_idle");
+
+        let mut texture_atlases = world.resource_mut::<Assets<TextureAtlas>>();
+        EditorAssets {
+            tower,
+            sanctuary: texture_atlases.add(TextureAtlas::from_grid(sanctuary_handle, Vec2::new(SANCTUARY_WIDTH, SANCTUARY_HEIGHT), 2, 1, Some(Vec2::new(1., 0.)), None)),
+            skeleton,
+        }
+    }
+}
+
+// Sprites of the edited objects, spawned again after every change
+#[derive(Component)]
+struct EditorSprite;
+
+#[derive(Component)]
+struct EditorText;
+
+// The saved map, unless another seed was typed on the menu
+fn open_editor(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    seed_input: Res<SeedInput>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+) {
+    let path = Path::new(EDITOR_MAP_PATH);
+    let new_map = || (EditorMap::from_seed(game_config.seed), format!("new map from seed {}, Ctrl+S replaces {}", game_config.seed, EDITOR_MAP_PATH));
+    let (map, message) = if path.exists() {
+        match EditorMap::load(path) {
+            Ok(map) if seed_input.typed_seed().is_some_and(|seed| seed != map.seed) => new_map(),
+            Ok(map) => (map, format!("{} loaded", EDITOR_MAP_PATH)),
+            Err(e) => {
+                warn!(target: "zelda::world", "{}", e);
+                (EditorMap::from_seed(game_config.seed), e)
+            },
+        }
+    } else {
+        new_map()
+    };
+    commands.insert_resource(Editor::new(map, message));
+
+    for (mut transform, mut projection) in camera_query.iter_mut() {
+        transform.translation.x = 0.;
+        transform.translation.y = 0.;
+        transform.rotation = Quat::IDENTITY;
+        projection.scale = EDITOR_CAMERA_SCALE;
+    }
+
+    commands.spawn((TextBundle::from_section("", TextStyle {
+        font_size: 20.0,
+        color: Color::WHITE,
+        ..Default::default()
+    }).with_style(Style {
+        position_type: PositionType::Absolute,
+        left: Val::Px(10.0),
+        top: Val::Px(10.0),
+        ..default()
+    }).with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.6)), EditorText));
+}
+
+fn close_editor(
+    mut commands: Commands,
+    editor_query: Query<Entity, Or<(With<EditorSprite>, With<EditorText>, With<TileLayer>)>>,
+) {
+    for entity in editor_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<Editor>();
+}
+
+fn cursor_position(
+    window_query: &Query<&Window, With<PrimaryWindow>>,
+    camera_query: &Query<(&Camera, &GlobalTransform)>,
+) -> Option<Vec2> {
+    let cursor = window_query.get_single().ok()?.cursor_position()?;
+    let (camera, transform) = camera_query.get_single().ok()?;
+    camera.viewport_to_world_2d(transform, cursor)
+}
+
+fn choose_tool(
+    mut editor: ResMut<Editor>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    for (tool, key) in EditorTool::ALL.into_iter().zip(EditorTool::KEYS) {
+        if keyboard_input.just_pressed(key) && editor.tool != tool {
+            editor.tool = tool;
+            editor.variant = 0;
+        }
+    }
+    // Variant of the selected object, or of the next one placed
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        match editor.selected {
+            Some(selection) => {
+                if editor.map.next_variant(selection) {
+                    editor.changed(selection);
+                }
+            },
+            None => editor.variant = (editor.variant + 1) % editor.tool.variants(),
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Q) {
+        editor.selected = None;
+    }
+    if keyboard_input.just_pressed(KeyCode::G) {
+        editor.snap = !editor.snap;
+    }
+}
+
+fn move_editor_camera(
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+) {
+    let mut direction = Vec2::ZERO;
+    // Ctrl+S saves the map, the camera stays where it is
+    if !keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        if keyboard_input.pressed(KeyCode::W) { direction.y += 1.; }
+        if keyboard_input.pressed(KeyCode::S) { direction.y -= 1.; }
+        if keyboard_input.pressed(KeyCode::A) { direction.x -= 1.; }
+        if keyboard_input.pressed(KeyCode::D) { direction.x += 1.; }
+    }
+    let zoom: f32 = wheel_events.iter().map(|event| 1. - event.y * 0.1).product();
+
+    for (mut transform, mut projection) in camera_query.iter_mut() {
+        projection.scale = (projection.scale * zoom).clamp(CAMERA_MIN_SCALE, CAMERA_MAX_SCALE);
+        let step = direction.normalize_or_zero() * EDITOR_CAMERA_SPEED * projection.scale * time.delta_seconds();
+        let position = (transform.translation.truncate() + step).clamp(Vec2::splat(-MAP_SIZE / 2.), Vec2::splat(MAP_SIZE / 2.));
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+// Left click selects and drags an object, or places one of the tool on empty ground, right click deletes
+fn edit_with_mouse(
+    mut editor: ResMut<Editor>,
+    mouse_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+) {
+    let Some(cursor) = cursor_position(&window_query, &camera_query) else {
+        return;
+    };
+
+    if mouse_input.just_pressed(MouseButton::Left) {
+        let selection = match editor.map.object_at(cursor.x, cursor.y) {
+            Some(selection) => selection,
+            None => {
+                let position = editor.snap(cursor);
+                let (tool, variant) = (editor.tool, editor.variant);
+                let selection = editor.map.place(tool, variant, position.x, position.y);
+                editor.changed(selection);
+                selection
+            },
+        };
+        let (x, y) = editor.map.position(selection);
+        editor.selected = Some(selection);
+        editor.drag_offset = Some(Vec2::new(x, y) - cursor);
+    }
+
+    if let (Some(selection), Some(offset)) = (editor.selected, editor.drag_offset) {
+        if !mouse_input.pressed(MouseButton::Left) {
+            editor.drag_offset = None;
+        } else if !mouse_input.just_pressed(MouseButton::Left) {
+            let position = editor.snap(cursor + offset);
+            if editor.map.position(selection) != (position.x, position.y) {
+                editor.map.move_to(selection, position.x, position.y);
+                editor.changed(selection);
+            }
+        }
+    }
+
+    let deleted = if mouse_input.just_pressed(MouseButton::Right) {
+        editor.map.object_at(cursor.x, cursor.y)
+    } else if keyboard_input.any_just_pressed([KeyCode::Delete, KeyCode::Back]) {
+        editor.selected
+    } else {
+        None
+    };
+    if let Some(selection) = deleted {
+        if editor.map.remove(selection) {
+            editor.changed(selection);
+            editor.selected = None;
+            editor.drag_offset = None;
+        } else {
+            editor.message = "the tower can only be moved".to_string();
+        }
+    }
+}
+
+// Arrows move the hitbox of the selected prop, with shift they resize it, R gives the default one back
+fn edit_collision_box(
+    mut editor: ResMut<Editor>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    let Some(Selection::Prop(index)) = editor.selected else {
+        return;
+    };
+    if keyboard_input.just_pressed(KeyCode::R) {
+        editor.map.plan.props[index].collision = None;
+        editor.changed(Selection::Prop(index));
+        return;
+    }
+
+    let mut change = Vec2::ZERO;
+    if keyboard_input.just_pressed(KeyCode::Up) { change.y += EDITOR_BOX_STEP; }
+    if keyboard_input.just_pressed(KeyCode::Down) { change.y -= EDITOR_BOX_STEP; }
+    if keyboard_input.just_pressed(KeyCode::Left) { change.x -= EDITOR_BOX_STEP; }
+    if keyboard_input.just_pressed(KeyCode::Right) { change.x += EDITOR_BOX_STEP; }
+    if change == Vec2::ZERO {
+        return;
+    }
+
+    let prop = &mut editor.map.plan.props[index];
+    // Bushes have no hitbox, editing one starts from the sprite
+    let (x, y, w, h) = prop.footprint();
+    let (mut offset_x, mut offset_y, mut w, mut h) = (x - prop.x, y - prop.y, w, h);
+    if keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        w = (w + change.x).max(EDITOR_BOX_STEP);
+        h = (h + change.y).max(EDITOR_BOX_STEP);
+    } else {
+        offset_x += change.x;
+        offset_y += change.y;
+    }
+    prop.collision = Some((offset_x, offset_y, w, h));
+    editor.changed(Selection::Prop(index));
+}
+
+// Ctrl+S saves, F5 saves and plays the map, Escape goes back to the menu
+fn save_or_leave_editor(
+    mut editor: ResMut<Editor>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut state: ResMut<NextState<GameState>>,
+    mut game_config: ResMut<GameConfig>,
+    mut custom_world: ResMut<CustomWorld>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        if editor.saved || editor.leaving {
+            state.set(GameState::Menu);
+        } else {
+            editor.leaving = true;
+            editor.message = "unsaved changes, Escape again to leave without saving, Ctrl+S to save".to_string();
+        }
+        return;
+    }
+    let control = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let play = keyboard_input.just_pressed(KeyCode::F5);
+    if !play && !(control && keyboard_input.just_pressed(KeyCode::S)) {
+        return;
+    }
+
+    if editor.map.plan.sanctuaries.is_empty() {
+        editor.message = "a map needs at least one shrine".to_string();
+        return;
+    }
+    match editor.map.save(Path::new(EDITOR_MAP_PATH)) {
+        Ok(()) => {
+            info!(target: "zelda::world", "map saved to {}", EDITOR_MAP_PATH);
+            editor.message = format!("saved to {}", EDITOR_MAP_PATH);
+            editor.saved = true;
+            editor.leaving = false;
+        },
+        Err(e) => {
+            warn!(target: "zelda::world", "could not save map: {}", e);
+            editor.message = e;
+            return;
+        },
+    }
+    if play {
+        game_config.seed = editor.map.seed;
+        custom_world.0 = Some(editor.map.plan.clone());
+        state.set(GameState::Loading);
+    }
+}
+
+// The paths follow the structures, the terrain is only rebuilt once they are dropped
+fn spawn_editor_terrain(
+    mut commands: Commands,
+    mut editor: ResMut<Editor>,
+    tile_query: Query<Entity, With<TileLayer>>,
+    mut tile_assets: ResMut<TileAssets>,
+    asset_server: Res<AssetServer>,
+) {
+    if !editor.terrain_changed || editor.drag_offset.is_some() {
+        return;
+    }
+    editor.terrain_changed = false;
+    for entity in tile_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    tile_assets.set_background(&asset_server, editor.map.plan.background.as_deref());
+    let tile_map = terrain_for_plan(editor.map.seed, &editor.map.plan);
+    for chunk in ChunkCoord::all() {
+        spawn_chunk_tiles(&mut commands, &tile_map, &tile_assets, chunk);
+    }
+}
+
+fn spawn_editor_objects(
+    mut commands: Commands,
+    mut editor: ResMut<Editor>,
+    sprite_query: Query<Entity, With<EditorSprite>>,
+    prop_atlases: Res<PropAtlases>,
+    assets: Res<EditorAssets>,
+) {
+    if !editor.objects_changed {
+        return;
+    }
+    editor.objects_changed = false;
+    for entity in sprite_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let plan = &editor.map.plan;
+    for prop in plan.props.iter() {
+        let entity = spawn_prop(&mut commands, &prop_atlases, prop);
+        commands.entity(entity).insert(EditorSprite);
+    }
+    commands.spawn((SpriteBundle {
+        texture: assets.tower.clone(),
+        sprite: Sprite {
+            custom_size: Some(Vec2::new(TOWER_WIDTH, TOWER_HEIGHT)),
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(plan.tower.0, plan.tower.1, Z_LAYER_WORLD),
+        ..Default::default()
+    }, YSort::new(TOWER_HEIGHT / 2.), EditorSprite));
+    for &(x, y) in plan.sanctuaries.iter() {
+        commands.spawn((SpriteSheetBundle {
+            texture_atlas: assets.sanctuary.clone(),
+            transform: Transform::from_xyz(x, y, Z_LAYER_WORLD),
+            ..Default::default()
+        }, YSort::new(SANCTUARY_HEIGHT / 2.), EditorSprite));
+    }
+    for &(x, y, guard) in plan.ennemies.iter().flatten() {
+        let mut sprite = TextureAtlasSprite::new(0);
+        if guard {
+            sprite.color = Color::ORANGE;
+        }
+        commands.spawn((SpriteSheetBundle {
+            texture_atlas: assets.skeleton.clone(),
+            transform: Transform {
+                translation: Vec3::new(x, y, Z_EDITOR_MARKERS),
+                scale: Vec3::new(ENNEMY_SPRITE_SCALE, ENNEMY_SPRITE_SCALE, 1.),
+                ..Default::default()
+            },
+            sprite,
+            ..Default::default()
+        }, EditorSprite));
+    }
+}
+
+fn draw_editor_boxes(
+    mut gizmos: Gizmos,
+    editor: Res<Editor>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+) {
+    let plan = &editor.map.plan;
+    for prop in plan.props.iter() {
+        if let Some(hitbox) = prop.hitbox() {
+            let (x, y, w, h) = hitbox.get_hitbox();
+            gizmos.rect_2d(Vec2::new(x, y), 0., Vec2::new(w, h), Color::RED);
+        }
+    }
+    for (selection, (x, y, w, h)) in editor.map.objects() {
+        if selection.is_structure() {
+            gizmos.rect_2d(Vec2::new(x, y), 0., Vec2::new(w, h), Color::ORANGE);
+        }
+        if Some(selection) == editor.selected {
+            gizmos.rect_2d(Vec2::new(x, y), 0., Vec2::new(w, h) + 4., Color::YELLOW);
+        }
+    }
+    if let Some(cursor) = cursor_position(&window_query, &camera_query) {
+        let position = editor.snap(cursor);
+        gizmos.circle_2d(position, 3., Color::WHITE);
+    }
+}
+
+fn update_editor_text(
+    editor: Res<Editor>,
+    mut text_query: Query<&mut Text, With<EditorText>>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+    let selected = match editor.selected {
+        Some(selection) => format!("{:?}", selection),
+        None => "nothing".to_string(),
+    };
+    let content = format!(
+        "Seed: {}   Tool: {:?} {}/{}   Snap: {}   Selected: {}{}\n{}\n\
+         1-6 tool, Tab variant, Q deselect, G snap, WASD/wheel camera, left click place/drag, right click/Delete remove\n\
+         Arrows move the hitbox of the selected prop, Shift+Arrows resize it, R resets it\n\
+         Ctrl+S save, F5 save and play, Escape menu",
+        editor.map.seed, editor.tool, editor.variant + 1, editor.tool.variants(),
+        if editor.snap { "on" } else { "off" },
+        selected,
+        if editor.saved { "" } else { "   (unsaved)" },
+        editor.message);
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = content.clone();
+    }
+}
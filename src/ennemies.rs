@@ -10,8 +10,16 @@ use crate::entitypattern::EntityBehavior;
 use crate::entitypattern::EntityPatern;
 use crate::entitypattern::FacingDirection;
 use crate::player::*;
-
-#[derive(Clone, Copy, PartialEq)]
+use crate::ai::Brain;
+use crate::rng::GameRng;
+use crate::tick::*;
+use crate::events::{PlayerDamaged, PlayerBlocked};
+use crate::tilemap::TileMap;
+use crate::chunks::ChunkCoord;
+use crate::ysort::YSort;
+use crate::animation::{AnimationEvent, AnimationLibrary, Animator};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum EnnemyState {
     Loading,
     Idle,
@@ -25,85 +33,73 @@ pub enum EnnemyState {
     // add more states later
 }
 
+impl EnnemyState {
+    fn clip(&self) -> &'static str {
+        match self {
+            EnnemyState::Loading | EnnemyState::Idle => "skeleton_idle",
+            EnnemyState::Roaming => "skeleton_walk",
+            EnnemyState::Chasing => "skeleton_run",
+            EnnemyState::Damaged => "skeleton_take_hit",
+            EnnemyState::Attacking => "skeleton_attack",
+            EnnemyState::Blocking => "skeleton_shield",
+            EnnemyState::Dying | EnnemyState::Dead => "skeleton_death",
+        }
+    }
+}
+
 pub struct EnnemyPlugin;
 
 impl Plugin for EnnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnExit(GameState::Menu), summon_ennemies)
-        .add_systems(OnExit(GameState::Loading), summon_ennemies)
-            .add_systems(Update, (game_ready.run_if(run_once()),
-                                                    update_ennemy_position, 
+        app.add_systems(FixedUpdate, (game_ready.run_if(run_once()),
+                                                    apply_difficulty,
                                                     update_ennemy_hitbox,
                                                     ennemy_attack, 
                                                     despawn_on_death,
-                                                    ennemy_aggro_detection,
                                                     state_speed_update,
-                                                    update_ennemy_sprite,
-                                                    change_sprite_orientation).run_if(in_state(GameState::Playing)));  
+                                                    update_ennemy_animation,
+                                                    ).chain().in_set(TickSet::Ennemies).run_if(in_state(GameState::Playing)))
+            .add_systems(FixedUpdate, commit_positions::<Ennemy>.in_set(TickSet::Commit).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, change_sprite_orientation.run_if(in_state(GameState::Playing)));  
     }
 }
 
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Ennemy {
     self_entity: EntityPatern,
     current_speed: f32,
     direction_counter: i32,
     state: EnnemyState,
+    max_health: i32,
     attack: i32,
     defense_ratio: f32, // chance to block an attack
+    difficulty_applied: bool,
 
-    roaming_frame_counter: usize,
-    roaming_frame_time: f32,
-
-    chasing_frame_counter: usize,
-    chasing_frame_time: f32,
-
-    damaged_frame_counter: usize,
-    damaged_frame_time: f32,
-
-    attacking_frame_counter: usize,
-    attacking_frame_time: f32,
+    // The Hit of the attack clip was shown, the player is hit as soon as they are in range
+    hit_pending: bool,
     attacking_has_hit: bool,
-
-    blocking_frame_counter: usize,
-    blocking_frame_time: f32,
-
-    dying_frame_counter: usize,
-    dying_frame_time: f32,
-
 }
 
 impl Ennemy {
 
     pub fn new(x: f32, y: f32, health: i32, attack: i32, defense_ratio: f32) -> Self {
+        Self::new_with_hitbox(x, y, ENNEMY_HITBOX_WIDTH, ENNEMY_HITBOX_HEIGHT, health, attack, defense_ratio)
+    }
+
+    pub fn new_with_hitbox(x: f32, y: f32, w: f32, h: f32, health: i32, attack: i32, defense_ratio: f32) -> Self {
         Self {
-            self_entity: EntityPatern::new(x, y, ENNEMY_HITBOX_WIDTH, ENNEMY_HITBOX_HEIGHT, health),
+            self_entity: EntityPatern::new(x, y, w, h, health),
             current_speed: ENNEMY_NORMAL_SPEED,
             direction_counter: 0,
             state: EnnemyState::Loading,
+            max_health: health,
             attack,
             defense_ratio,
+            difficulty_applied: false,
 
-            roaming_frame_counter: 0,
-            roaming_frame_time: 0.,
-
-            chasing_frame_counter: 0,
-            chasing_frame_time: 0.,
-
-            damaged_frame_counter: 0,
-            damaged_frame_time: 0.,
-
-            attacking_frame_counter: 0,
-            attacking_frame_time: 0.,
+            hit_pending: false,
             attacking_has_hit: false,
-
-            blocking_frame_counter: 0,
-            blocking_frame_time: 0.,
-
-            dying_frame_counter: 0,
-            dying_frame_time: 0.,
-
         }
     }
 
@@ -172,21 +168,38 @@ impl Ennemy {
     }
 
 
-    fn chase_player(&mut self, player: &Player, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
-        if !self.is_attacking() { self.state = EnnemyState::Chasing;}
+    fn step(&self) -> f32 {
+        self.current_speed * FIXED_TIMESTEP
+    }
+
+    pub fn chase_player(&mut self, player: &Player, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
         let (x, y) = player.get_pos();
+        self.go_to(x, y, true, collision_query);
+    }
+
+    pub fn go_to(&mut self, x: f32, y: f32, running: bool, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
+        if !self.is_attacking() {
+            self.state = if running { EnnemyState::Chasing } else { EnnemyState::Roaming };
+        }
+        self.walk_towards(x, y, collision_query);
+    }
+
+    fn walk_towards(&mut self, x: f32, y: f32, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
         let dx = x - self.x();  // Difference in x positions
         let dy = y - self.y();  // Difference in y positions
     
         // Normalize the direction vector (dx, dy)
         let distance = (dx*dx + dy*dy).sqrt();
+        if distance == 0. {
+            return;
+        }
         let dx = dx / distance;
         let dy = dy / distance;
 
     
         let mut facing_direction: Option<FacingDirection> = None;
     
-        let new_x = self.x() + dx * self.current_speed;
+        let new_x = self.x() + dx * self.step();
 
         if new_x < self.x() {
             facing_direction = Some(FacingDirection::Left);
@@ -194,7 +207,7 @@ impl Ennemy {
             facing_direction = Some(FacingDirection::Right);
         }
 
-        let new_y = self.y() + dy * self.current_speed;
+        let new_y = self.y() + dy * self.step();
 
         if new_y < self.y() {
             if let Some(direction) = facing_direction {
@@ -218,16 +231,15 @@ impl Ennemy {
             }
         }
         if let Some(direction) = facing_direction {
-            self.move_in_direction(&direction, self.current_speed, collision_query);
+            self.move_in_direction(&direction, self.step(), collision_query);
         }
     }
     
 
-    fn roaming(&mut self, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
+    pub fn roaming(&mut self, rng: &mut StdRng, collision_query: &Query<&CollisionComponent, Without<Ennemy>>) {
         let new_direction: Option<FacingDirection>;
         if self.direction_counter <= 0 {
             // Choisir une nouvelle direction
-            let mut rng = rand::thread_rng();
 
             let direction = rng.gen_range(0..17);
             new_direction = Some(match direction {
@@ -251,13 +263,41 @@ impl Ennemy {
         }
         if !self.is_idle() {
             if let Some(ref direction) = new_direction{
-                self.move_in_direction(direction, self.current_speed, collision_query);
+                self.move_in_direction(direction, self.step(), collision_query);
             }
         }
 
         self.direction_counter -= 1;
     }
 
+    #[cfg(feature = "debug")]
+    pub fn state(&self) -> EnnemyState {
+        self.state
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn kill(&mut self) {
+        let health = self.self_entity.health();
+        self.self_entity.add_health(-health);
+        self.state = EnnemyState::Dying;
+    }
+
+    pub fn health(&self) -> i32 {
+        self.self_entity.health()
+    }
+
+    fn scale_stats(&mut self, multiplier: f32) {
+        let health = (self.max_health as f32 * multiplier).round() as i32;
+        self.self_entity.add_health(health - self.max_health);
+        self.max_health = health;
+        self.attack = (self.attack as f32 * multiplier).round() as i32;
+        self.difficulty_applied = true;
+    }
+
+    pub fn max_health(&self) -> i32 {
+        self.max_health
+    }
+
     fn is_taking_damage(&self) -> bool {
         self.state == EnnemyState::Damaged
     }
@@ -270,19 +310,23 @@ impl Ennemy {
         self.state == EnnemyState::Blocking
     }
 
-    fn is_dying(&self) -> bool {
+    pub fn is_dying(&self) -> bool {
         self.state == EnnemyState::Dying
     }
 
 
-    fn is_doing_something(&self) -> bool {
+    pub fn is_doing_something(&self) -> bool {
         self.is_taking_damage() || self.is_blocking() || self.is_dying() || self.is_dead()
     }
 
-    fn is_dead(&self) -> bool {
+    pub fn is_dead(&self) -> bool {
         self.state == EnnemyState::Dead
     }
 
+    pub fn is_chasing(&self) -> bool {
+        self.state == EnnemyState::Chasing
+    }
+
     fn is_loading(&self) -> bool {
         self.state == EnnemyState::Loading
     }
@@ -299,36 +343,32 @@ impl Collisionable for Ennemy {
     }
 
     fn get_hitbox(&self) -> (f32, f32, f32, f32) {
-        (self.x(), self.y(), ENNEMY_HITBOX_WIDTH, ENNEMY_HITBOX_HEIGHT)
+        self.self_entity.get_hitbox()
     }
 }
 
 impl EntityBehavior for Ennemy {
-    fn attack(&mut self, target: &mut dyn EntityBehavior) -> bool {
+    fn attack(&mut self, target: &mut dyn EntityBehavior, rng: &mut StdRng) -> bool {
         if !self.is_taking_damage() {
             self.state = EnnemyState::Attacking;
         }
-        if self.attacking_frame_counter == 6 && !self.attacking_has_hit{
+        if self.hit_pending && !self.attacking_has_hit{
+            self.hit_pending = false;
             self.attacking_has_hit = true;
-            return target.get_attacked(self.attack);
+            return target.get_attacked(self.attack, rng);
         }
         false
     }
 
-    fn get_attacked(&mut self, damage: i32) -> bool {
-        if rand::random::<f32>() > self.defense_ratio {
+    fn get_attacked(&mut self, damage: i32, rng: &mut StdRng) -> bool {
+        if rng.gen::<f32>() > self.defense_ratio {
             self.take_damage(damage);
             if self.self_entity.health() <= 0 {
-                println!("ennemy died");
                 self.state = EnnemyState::Dying;
             }
-            else {
-                println!("ennemy health lowered: {}", self.self_entity.health());
-            }
             return true;
         }
         self.state = EnnemyState::Blocking;
-        println!("ennemy blocked attack");
         false
     }
 
@@ -371,67 +411,74 @@ impl EntityBehavior for Ennemy {
     }
 }
 
-fn summon_ennemy(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>, 
-    texture_atlases: &mut ResMut<Assets<TextureAtlas>>,
-    game_config: &Res<GameConfig>,
-    nb: i32
-) {
-    
-    let texture_handle = asset_server.load("Skeleton/Idle.png");
-    let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(150., 150.), 4, 1, Some(Vec2::new(0., 0.)), Some(Vec2::new(0., 0.)));
-    let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
-
-
-    let mut rng = StdRng::seed_from_u64(game_config.seed + OFFSET_ENNEMY + nb as u64);
-
-    let max_value_x = MAP_SIZE / 2. - SANCTUARY_WIDTH / 2.;
-    let max_value_y = MAP_SIZE / 2. - SANCTUARY_HEIGHT / 2.;
-
-    let mut x: f32;
-    let mut y: f32;
-
-    loop {
-        x = rng.gen_range(-max_value_x..max_value_x);
-        y = rng.gen_range(-max_value_y..max_value_y);
-        if (x< -ENNEMY_AGGRO_DISTANCE || x > ENNEMY_AGGRO_DISTANCE) && (y < -ENNEMY_AGGRO_DISTANCE || y > ENNEMY_AGGRO_DISTANCE) {
-            break;
+// Enemies met the first time a chunk loads (position and whether they guard a shrine), only from the seed and the chunk
+pub fn chunk_ennemies(tile_map: &TileMap, seed: u64, chunk: ChunkCoord) -> Vec<(f32, f32, bool)> {
+    let mut rng = chunk.rng(seed.wrapping_add(OFFSET_ENNEMY));
+    let (center_x, center_y) = chunk.center();
+    let half = CHUNK_SIZE / 2.;
+
+    let mut ennemies = Vec::new();
+    for _ in 0..ENNEMIES_PER_CHUNK {
+        let x = rng.gen_range(center_x - half..center_x + half);
+        let y = rng.gen_range(center_y - half..center_y + half);
+        let guard = rng.gen_ratio(1, ENNEMY_GUARD_RATIO as u32);
+        if (x< -ENNEMY_AGGRO_DISTANCE || x > ENNEMY_AGGRO_DISTANCE) && (y < -ENNEMY_AGGRO_DISTANCE || y > ENNEMY_AGGRO_DISTANCE)
+            && tile_map.is_area_free(x, y, ENNEMY_HITBOX_WIDTH, ENNEMY_HITBOX_HEIGHT) {
+            ennemies.push((x, y, guard));
         }
     }
+    ennemies
+}
+
+pub fn spawn_ennemy(
+    commands: &mut Commands,
+    library: &AnimationLibrary,
+    x: f32,
+    y: f32,
+) -> Entity {
+    spawn_ennemy_with_stats(commands, library, x, y, ENNEMY_HEALTH, ENNEMY_ATTACK)
+}
+
+pub fn spawn_ennemy_with_stats(
+    commands: &mut Commands,
+    library: &AnimationLibrary,
+    x: f32,
+    y: f32,
+    health: i32,
+    attack: i32,
+) -> Entity {
+    let ennemy: Ennemy = Ennemy::new(x, y, health, attack, ENNEMY_DEFENCE_RATIO);
+    spawn_ennemy_from(commands, library, ennemy)
+}
 
-    let ennemy: Ennemy = Ennemy::new(x, y, 10, 5, ENNEMY_DEFENCE_RATIO);
+pub fn spawn_ennemy_from(
+    commands: &mut Commands,
+    library: &AnimationLibrary,
+    ennemy: Ennemy,
+) -> Entity {
+    let (x, y) = (ennemy.x(), ennemy.y());
     let hitbox = CollisionComponent::new(ennemy.x(), ennemy.y(), ENNEMY_HITBOX_WIDTH, ENNEMY_HITBOX_HEIGHT);
     let entity = (SpriteSheetBundle {
-        texture_atlas: texture_atlas_handle.clone(),
+        texture_atlas: library.atlas("skeleton_idle"),
         transform: Transform {
-            translation: Vec3::new(ennemy.x() as f32, ennemy.y() as f32, Z_LAYER_ENNEMIES),
+            translation: Vec3::new(ennemy.x() as f32, ennemy.y() as f32, Z_LAYER_WORLD),
             scale: Vec3::new(ENNEMY_SPRITE_SCALE, ENNEMY_SPRITE_SCALE, 1.),
             ..Default::default()
         },
         sprite: TextureAtlasSprite::new(0),
         ..Default::default()
-    }, ennemy, hitbox);
-    commands.spawn(entity);
+    }, SimPosition::new(x, y), ennemy, hitbox, Brain::skeleton(), YSort::new(ENNEMY_HITBOX_HEIGHT / 2.), Animator::new("skeleton_idle"));
+    commands.spawn(entity).id()
 }
 
-fn summon_ennemies(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>, 
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+fn apply_difficulty(
+    mut query: Query<&mut Ennemy, Added<Ennemy>>,
     game_config: Res<GameConfig>,
 ) {
-    for i in 0..ENNEMIES_NUMBER {
-        summon_ennemy(&mut commands, &asset_server, &mut texture_atlases, &game_config, i);
-    }
-}
-
-fn update_ennemy_position(
-    mut query: Query<(&mut Transform, &Ennemy)>,
-) {
-    for (mut transform, ennemy) in query.iter_mut() {
-        transform.translation = Vec3::new(ennemy.x() as f32, ennemy.y() as f32, Z_LAYER_ENNEMIES);
+    let multiplier = game_config.difficulty.ennemy_multiplier();
+    // Enemies coming back from an unloaded chunk already have their stats
+    for mut ennemy in query.iter_mut().filter(|ennemy| !ennemy.difficulty_applied) {
+        ennemy.scale_stats(multiplier);
     }
 }
 
@@ -442,57 +489,70 @@ fn update_ennemy_hitbox(
 }
 
 fn ennemy_attack(
-    mut ennemy_query: Query<&mut Ennemy>,
+    mut ennemy_query: Query<(&mut Ennemy, &Animator)>,
     mut player_query: Query<&mut Player>,
+    mut game_rng: ResMut<GameRng>,
+    mut damaged_events: EventWriter<PlayerDamaged>,
+    mut blocked_events: EventWriter<PlayerBlocked>,
 ) {
     let mut player = player_query.single_mut();
-    for mut ennemy in ennemy_query.iter_mut() {
+    for (mut ennemy, animator) in ennemy_query.iter_mut() {
+        if animator.fired(EnnemyState::Attacking.clip(), AnimationEvent::Hit) {
+            ennemy.hit_pending = true;
+        }
         if !ennemy.is_blocking() && !ennemy.is_dying() && !ennemy.is_dead() && !ennemy.is_loading() {
             if let Some(direction) = ennemy.facing_direction() {
                 let actual_player: &mut Player = &mut player;
                 if actual_player.is_aggroable() {
+                let health_before = actual_player.health();
+                let had_hit = ennemy.attacking_has_hit;
                 match direction {
                     FacingDirection::Up => {
                         if ennemy.would_collide(ennemy.x(), ennemy.y() + ENNEMY_ATTACK_RANGE, &actual_player.get_collision_component()) && collisions::equals(&direction, actual_player.get_relative_position(&ennemy.get_collision_component())) {
-                            ennemy.attack(actual_player);
+                            ennemy.attack(actual_player, &mut game_rng.combat);
                         }
                     },
                     FacingDirection::Down => {
                         if ennemy.would_collide(ennemy.x(), ennemy.y() - ENNEMY_ATTACK_RANGE, &actual_player.get_collision_component()) && collisions::equals(&direction, actual_player.get_relative_position(&ennemy.get_collision_component())) {
-                            ennemy.attack(actual_player);
+                            ennemy.attack(actual_player, &mut game_rng.combat);
                         }
                     },
                     FacingDirection::Left => {
                         if ennemy.would_collide(ennemy.x() - ENNEMY_ATTACK_RANGE, ennemy.y(), &actual_player.get_collision_component()) && collisions::equals(&direction, actual_player.get_relative_position(&ennemy.get_collision_component())) {
-                            ennemy.attack(actual_player);
+                            ennemy.attack(actual_player, &mut game_rng.combat);
                         }
                     },
                     FacingDirection::Right => {
                         if ennemy.would_collide(ennemy.x() + ENNEMY_ATTACK_RANGE, ennemy.y(), &actual_player.get_collision_component()) && collisions::equals(&direction, actual_player.get_relative_position(&ennemy.get_collision_component())) {
-                            ennemy.attack(actual_player);
+                            ennemy.attack(actual_player, &mut game_rng.combat);
                         }
                     },
                     FacingDirection::TopLeft => {
                         if ennemy.would_collide(ennemy.x() - ENNEMY_ATTACK_RANGE, ennemy.y() + ENNEMY_ATTACK_RANGE, &actual_player.get_collision_component()) && collisions::equals(&direction, actual_player.get_relative_position(&ennemy.get_collision_component())) {
-                            ennemy.attack(actual_player);
+                            ennemy.attack(actual_player, &mut game_rng.combat);
                         }
                     },
                     FacingDirection::TopRight => {
                         if ennemy.would_collide(ennemy.x() + ENNEMY_ATTACK_RANGE, ennemy.y() + ENNEMY_ATTACK_RANGE, &actual_player.get_collision_component()) && collisions::equals(&direction, actual_player.get_relative_position(&ennemy.get_collision_component())) {
-                            ennemy.attack(actual_player);
+                            ennemy.attack(actual_player, &mut game_rng.combat);
                         }
                     },
                     FacingDirection::BottomLeft => {
                         if ennemy.would_collide(ennemy.x() - ENNEMY_ATTACK_RANGE, ennemy.y() - ENNEMY_ATTACK_RANGE, &actual_player.get_collision_component()) && collisions::equals(&direction, actual_player.get_relative_position(&ennemy.get_collision_component())) {
-                            ennemy.attack(actual_player);
+                            ennemy.attack(actual_player, &mut game_rng.combat);
                         }
                     },
                     FacingDirection::BottomRight => {
                         if ennemy.would_collide(ennemy.x() + ENNEMY_ATTACK_RANGE, ennemy.y() - ENNEMY_ATTACK_RANGE, &actual_player.get_collision_component()) && collisions::equals(&direction, actual_player.get_relative_position(&ennemy.get_collision_component())) {
-                            ennemy.attack(actual_player);
+                            ennemy.attack(actual_player, &mut game_rng.combat);
                         }
                     },
                 }
+                if actual_player.health() < health_before {
+                    damaged_events.send(PlayerDamaged { damage: health_before - actual_player.health(), health: actual_player.health() });
+                } else if !had_hit && ennemy.attacking_has_hit && actual_player.is_blocking() {
+                    blocked_events.send(PlayerBlocked);
+                }
             }}
             
         }
@@ -510,25 +570,6 @@ fn despawn_on_death(
     }
 }
 
-fn ennemy_aggro_detection(
-    mut ennemy_query: Query<(&mut Ennemy, &Transform)>,
-    player_query: Query<(&Player, &Transform)>,
-    collision_query: Query<&CollisionComponent, Without<Ennemy>>
-) {
-    let (player, player_transform) = player_query.single();
-    for (mut ennemy, transform) in ennemy_query.iter_mut() {
-        let distance = transform.translation.distance(player_transform.translation);
-        if !ennemy.is_doing_something() {
-            if distance < ENNEMY_AGGRO_DISTANCE && player.is_aggroable() {
-                ennemy.chase_player(&player, &collision_query);
-            
-            } else {
-                ennemy.roaming(&collision_query);
-            }
-        }    
-    }
-}
-
 fn state_speed_update(
     mut ennemy_query: Query<&mut Ennemy>
 )
@@ -542,161 +583,25 @@ fn state_speed_update(
     }
  }
 
- fn update_ennemy_sprite(
-    mut query: Query<(&mut Ennemy, &mut TextureAtlasSprite, &mut Handle<TextureAtlas>)>,
-    asset_server: Res<AssetServer>, 
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-    time: Res<Time>,
+fn update_ennemy_animation(
+    mut query: Query<(&mut Ennemy, &mut Animator)>,
 ) {
-    for (mut ennemy, mut sprite, mut texture) in query.iter_mut() {
-        match ennemy.state {
-            EnnemyState::Idle => {
-                let texture_handle = asset_server.load("Skeleton/Idle.png");
-                let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(150., 150.), 4, 1, Some(Vec2::new(0., 0.)), Some(Vec2::new(0., 0.)));
-                let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
-                *texture = texture_atlas_handle.clone();
-                
-                ennemy.roaming_frame_time += time.delta_seconds();
-                if ennemy.roaming_frame_time >= 0.3 {
-                    ennemy.roaming_frame_counter += 1;
-                    ennemy.roaming_frame_time = 0.;
-                }
-
-                if ennemy.roaming_frame_counter >= 4 {
-                    ennemy.roaming_frame_counter = 0;
-                }
-
-                sprite.index = ennemy.roaming_frame_counter;
-            },
-            EnnemyState::Roaming => {
-                
-                let texture_handle = asset_server.load("Skeleton/Walk.png");
-                let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(150., 150.), 4, 1, Some(Vec2::new(0., 0.)), Some(Vec2::new(0., 0.)));
-                let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
-                *texture = texture_atlas_handle.clone();
-                
-                ennemy.roaming_frame_time += time.delta_seconds();
-                if ennemy.roaming_frame_time >= 0.3 {
-                    ennemy.roaming_frame_counter += 1;
-                    ennemy.roaming_frame_time = 0.;
-                }
-
-                if ennemy.roaming_frame_counter >= 4 {
-                    ennemy.roaming_frame_counter = 0;
-                }
-
-                sprite.index = ennemy.roaming_frame_counter;
-            },
-            EnnemyState::Chasing => {
-                    
-                    let texture_handle = asset_server.load("Skeleton/Walk.png");
-                    let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(150., 150.), 4, 1, Some(Vec2::new(0., 0.)), Some(Vec2::new(0., 0.)));
-                    let texture_atlas_handle = texture_atlases.add(texture_atlas);
-    
-                    *texture = texture_atlas_handle.clone();
-                    
-                    ennemy.chasing_frame_time += time.delta_seconds();
-                    if ennemy.chasing_frame_time >= 0.1 {
-                        ennemy.chasing_frame_counter += 1;
-                        ennemy.chasing_frame_time = 0.;
-                    }
-    
-                    if ennemy.chasing_frame_counter >= 4 {
-                        ennemy.chasing_frame_counter = 0;
-                    }
-    
-                    sprite.index = ennemy.chasing_frame_counter;
-            },
-            EnnemyState::Damaged => {
-                let texture_handle = asset_server.load("Skeleton/Take Hit.png");
-                let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(150., 150.), 4, 1, Some(Vec2::new(0., 0.)), Some(Vec2::new(0., 0.)));
-                let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
-                *texture = texture_atlas_handle.clone();
-                
-                ennemy.damaged_frame_time += time.delta_seconds();
-                if ennemy.damaged_frame_time >= 0.2 {
-                    ennemy.damaged_frame_counter += 1;
-                    ennemy.damaged_frame_time = 0.;
-                }
-
-                if ennemy.damaged_frame_counter >= 4 {
-                    ennemy.damaged_frame_counter = 0;
-                    ennemy.state = EnnemyState::Chasing;
-                }   
-
-                sprite.index = ennemy.damaged_frame_counter;
-            },
-            EnnemyState::Attacking => {
-                let texture_handle = asset_server.load("Skeleton/Attack.png");
-                let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(150., 150.), 8, 1, Some(Vec2::new(0., 0.)), Some(Vec2::new(0., 0.)));
-                let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
-                *texture = texture_atlas_handle.clone();
-                
-                ennemy.attacking_frame_time += time.delta_seconds();
-                if ennemy.attacking_frame_time >= ENNEMY_ATTACK_SPEED/8. {
-                    ennemy.attacking_frame_counter += 1;
-                    ennemy.attacking_frame_time = 0.;
-                }
-
-                if ennemy.attacking_frame_counter >= 8 {
-                    ennemy.attacking_frame_counter = 0;
+    for (mut ennemy, mut animator) in query.iter_mut() {
+        if animator.fired(ennemy.state.clip(), AnimationEvent::End) {
+            match ennemy.state {
+                EnnemyState::Damaged | EnnemyState::Blocking => ennemy.state = EnnemyState::Idle,
+                EnnemyState::Attacking => {
                     ennemy.attacking_has_hit = false;
-                    ennemy.state = EnnemyState::Chasing;
-                }   
-
-                sprite.index = ennemy.attacking_frame_counter;
-            },
-            EnnemyState::Blocking => {
-                let texture_handle = asset_server.load("Skeleton/Shield.png");
-                let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(150., 150.), 4, 1, Some(Vec2::new(0., 0.)), Some(Vec2::new(0., 0.)));
-                let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
-                *texture = texture_atlas_handle.clone();
-                
-                ennemy.blocking_frame_time += time.delta_seconds();
-                if ennemy.blocking_frame_time >= 0.1 {
-                    ennemy.blocking_frame_counter += 1;
-                    ennemy.blocking_frame_time = 0.;
-                }
-
-                if ennemy.blocking_frame_counter >= 4 {
-                    ennemy.blocking_frame_counter = 0;
-                    ennemy.state = EnnemyState::Chasing;
-                }   
-
-                sprite.index = ennemy.blocking_frame_counter;
-            },
-            EnnemyState::Dying => {
-                let texture_handle = asset_server.load("Skeleton/Death.png");
-                let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(150., 150.), 4, 1, Some(Vec2::new(0., 0.)), Some(Vec2::new(0., 0.)));
-                let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
-                *texture = texture_atlas_handle.clone();
-                
-                ennemy.dying_frame_time += time.delta_seconds();
-                if ennemy.dying_frame_time >= 0.2 {
-                    ennemy.dying_frame_counter += 1;
-                    ennemy.dying_frame_time = 0.;
-                }
-
-                if ennemy.dying_frame_counter >= 4 {
-                    ennemy.dying_frame_counter = 0;
-                    ennemy.state = EnnemyState::Dead;
-                }   
-
-                sprite.index = ennemy.dying_frame_counter;
-            },
-            EnnemyState::Dead => {
-                sprite.index = 3;
-            },
-            EnnemyState::Loading => {
-                sprite.index = 0;
-            },
+                    ennemy.state = EnnemyState::Idle;
+                },
+                EnnemyState::Dying => ennemy.state = EnnemyState::Dead,
+                _ => (),
+            }
+        }
+        if !ennemy.is_attacking() {
+            ennemy.hit_pending = false;
         }
+        animator.play(ennemy.state.clip());
     }
 }
 
@@ -707,22 +612,22 @@ fn change_sprite_orientation(
         if let Some(direction) = ennemy.facing_direction() {
             match direction {
                 FacingDirection::Left => {
-                    transform.scale.x = -ENNEMY_SPRITE_SCALE;
+                    transform.scale.x = -transform.scale.x.abs();
                 },
                 FacingDirection::Right => {
-                    transform.scale.x = ENNEMY_SPRITE_SCALE;
+                    transform.scale.x = transform.scale.x.abs();
                 },
                 FacingDirection::TopLeft => {
-                    transform.scale.x = -ENNEMY_SPRITE_SCALE;
+                    transform.scale.x = -transform.scale.x.abs();
                 },
                 FacingDirection::TopRight => {
-                    transform.scale.x = ENNEMY_SPRITE_SCALE;
+                    transform.scale.x = transform.scale.x.abs();
                 },
                 FacingDirection::BottomLeft => {
-                    transform.scale.x = -ENNEMY_SPRITE_SCALE;
+                    transform.scale.x = -transform.scale.x.abs();
                 },
                 FacingDirection::BottomRight => {
-                    transform.scale.x = ENNEMY_SPRITE_SCALE;
+                    transform.scale.x = transform.scale.x.abs();
                 },
                 _ => (),
             }
@@ -1,3 +1,5 @@
+use rand::rngs::StdRng;
+
 #[derive(PartialEq, Clone, Copy)]
 pub enum FacingDirection {
     Up,
@@ -10,6 +12,7 @@ pub enum FacingDirection {
     BottomRight,
 }
 
+#[derive(Clone)]
 pub struct EntityPatern {
     x: f32,
     y: f32,
@@ -20,8 +23,8 @@ pub struct EntityPatern {
 }
 
 pub trait EntityBehavior {
-    fn attack(&mut self, target: &mut dyn EntityBehavior) -> bool;
-    fn get_attacked(&mut self, damage: i32) -> bool;
+    fn attack(&mut self, target: &mut dyn EntityBehavior, rng: &mut StdRng) -> bool;
+    fn get_attacked(&mut self, damage: i32, rng: &mut StdRng) -> bool;
     fn take_damage(&mut self, damage: i32) -> bool;
     fn x(&self) -> f32;
     fn y(&self) -> f32;
@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+use crate::animation::AnimationEventFired;
+
+pub struct GameEventsPlugin;
+
+impl Plugin for GameEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlayerAttacked>()
+            .add_event::<PlayerDamaged>()
+            .add_event::<PlayerBlocked>()
+            .add_event::<PlayerHealed>()
+            .add_event::<EnemyDamaged>()
+            .add_event::<EnemyBlocked>()
+            .add_event::<EnemyKilled>()
+            .add_event::<ShrineUnlocked>()
+            .add_event::<TowerActivated>()
+            .add_event::<BossSpawned>()
+            .add_event::<BossAreaAttack>()
+            .add_event::<BossDefeated>()
+            .add_systems(Update, (log_player_events,
+                                  log_enemy_events,
+                                  log_structure_events,
+                                  log_boss_events,
+                                  log_animation_events,
+                                  log_state_changes));
+    }
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct PlayerAttacked;
+
+#[derive(Event, Clone, Debug)]
+pub struct PlayerDamaged {
+    pub damage: i32,
+    pub health: i32,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct PlayerBlocked;
+
+#[derive(Event, Clone, Debug)]
+pub struct PlayerHealed {
+    pub health: i32,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct EnemyDamaged {
+    pub entity: Entity,
+    pub damage: i32,
+    pub health: i32,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct EnemyBlocked {
+    pub entity: Entity,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct EnemyKilled {
+    pub entity: Entity,
+    pub position: (f32, f32),
+    pub boss: bool,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct ShrineUnlocked {
+    pub entity: Entity,
+    pub position: (f32, f32),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TowerActivation {
+    ShrineRevealed,
+    ShrinesLocked,
+    BossAlive,
+    Freed,
+}
+
+impl TowerActivation {
+    // Refused when shrines are still locked or the boss is alive
+    pub fn is_success(&self) -> bool {
+        matches!(self, TowerActivation::ShrineRevealed | TowerActivation::Freed)
+    }
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct TowerActivated {
+    pub entity: Entity,
+    pub activation: TowerActivation,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct BossSpawned {
+    pub entity: Entity,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct BossAreaAttack {
+    pub entity: Entity,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct BossDefeated {
+    pub entity: Entity,
+}
+
+fn log_player_events(
+    mut attacked: EventReader<PlayerAttacked>,
+    mut damaged: EventReader<PlayerDamaged>,
+    mut blocked: EventReader<PlayerBlocked>,
+    mut healed: EventReader<PlayerHealed>,
+) {
+    for _ in attacked.iter() {
+        debug!(target: "zelda::combat", "player swung the sword");
+    }
+    for event in damaged.iter() {
+        info!(target: "zelda::combat", "player took {} damage, health now at {}", event.damage, event.health);
+    }
+    for _ in blocked.iter() {
+        debug!(target: "zelda::combat", "player blocked an attack");
+    }
+    for event in healed.iter() {
+        info!(target: "zelda::combat", "player healed, health now at {}", event.health);
+    }
+}
+
+fn log_enemy_events(
+    mut damaged: EventReader<EnemyDamaged>,
+    mut blocked: EventReader<EnemyBlocked>,
+    mut killed: EventReader<EnemyKilled>,
+) {
+    for event in damaged.iter() {
+        debug!(target: "zelda::combat", "enemy {:?} took {} damage, health now at {}", event.entity, event.damage, event.health);
+    }
+    for event in blocked.iter() {
+        debug!(target: "zelda::combat", "enemy {:?} blocked an attack", event.entity);
+    }
+    for event in killed.iter() {
+        let kind = if event.boss { "boss" } else { "enemy" };
+        info!(target: "zelda::combat", "{} {:?} killed at ({:.0}, {:.0})", kind, event.entity, event.position.0, event.position.1);
+    }
+}
+
+fn log_structure_events(
+    mut unlocked: EventReader<ShrineUnlocked>,
+    mut activated: EventReader<TowerActivated>,
+) {
+    for event in unlocked.iter() {
+        info!(target: "zelda::world", "shrine {:?} unlocked at ({:.0}, {:.0})", event.entity, event.position.0, event.position.1);
+    }
+    for event in activated.iter() {
+        let message = match event.activation {
+            TowerActivation::ShrineRevealed => "revealed a new shrine",
+            TowerActivation::ShrinesLocked => "needs every revealed shrine to be unlocked first",
+            TowerActivation::BossAlive => "is still guarded by its boss",
+            TowerActivation::Freed => "is freed",
+        };
+        info!(target: "zelda::world", "tower {:?} {}", event.entity, message);
+    }
+}
+
+fn log_boss_events(
+    mut spawned: EventReader<BossSpawned>,
+    mut area_attacks: EventReader<BossAreaAttack>,
+    mut defeated: EventReader<BossDefeated>,
+) {
+    for event in spawned.iter() {
+        info!(target: "zelda::boss", "boss {:?} spawned", event.entity);
+    }
+    for event in area_attacks.iter() {
+        debug!(target: "zelda::boss", "boss {:?} unleashed its area attack", event.entity);
+    }
+    for event in defeated.iter() {
+        info!(target: "zelda::boss", "boss {:?} defeated", event.entity);
+    }
+}
+
+fn log_animation_events(
+    mut fired: EventReader<AnimationEventFired>,
+) {
+    for event in fired.iter() {
+        trace!(target: "zelda::animation", "{:?} of {} for {:?}", event.event, event.clip, event.entity);
+    }
+}
+
+fn log_state_changes(
+    state: Res<State<GameState>>,
+) {
+    if state.is_changed() {
+        debug!(target: "zelda::state", "game state is now {:?}", state.get());
+    }
+}
@@ -0,0 +1,150 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::{Rgb, RgbImage};
+use serde::Serialize;
+
+use crate::chunks::ChunkCoord;
+use crate::collisions::Collisionable;
+use crate::constants::*;
+use crate::ennemies::chunk_ennemies;
+use crate::structures::{Sanctuary, Tower};
+use crate::tilemap::{TileKind, TileMap};
+use crate::worldgen::{Biome, PropKind, WorldPlan, plan_world, terrain_for_plan};
+
+pub struct ExportArgs {
+    pub out: Option<PathBuf>,
+    pub png: Option<PathBuf>,
+}
+
+// Everything a run would spawn, with the hitbox it collides with (center and size)
+#[derive(Serialize)]
+struct ExportedEntity {
+    kind: &'static str,
+    variant: Option<usize>,
+    x: f32,
+    y: f32,
+    hitbox: Option<(f32, f32, f32, f32)>,
+}
+
+#[derive(Serialize)]
+struct WorldExport {
+    seed: u64,
+    size: f32,
+    entities: Vec<ExportedEntity>,
+}
+
+fn exported_entities(seed: u64, tile_map: &TileMap, plan: &WorldPlan) -> Vec<ExportedEntity> {
+    let mut entities = Vec::new();
+    let tower = Tower::new(plan.tower.0, plan.tower.1);
+    entities.push(ExportedEntity { kind: "tower", variant: None, x: plan.tower.0, y: plan.tower.1, hitbox: Some(tower.get_hitbox()) });
+    for &(x, y) in plan.sanctuaries.iter() {
+        entities.push(ExportedEntity { kind: "shrine", variant: None, x, y, hitbox: Some(Sanctuary::new(x, y).get_hitbox()) });
+    }
+    for prop in plan.props.iter() {
+        let kind = match prop.kind {
+            PropKind::Tree => "tree",
+            PropKind::Bush => "bush",
+            PropKind::Grave => "grave",
+        };
+        entities.push(ExportedEntity { kind, variant: Some(prop.index), x: prop.x, y: prop.y, hitbox: prop.hitbox().map(|hitbox| hitbox.get_hitbox()) });
+    }
+
+    // The enemies met the first time each chunk loads
+    let ennemies = match &plan.ennemies {
+        Some(ennemies) => ennemies.clone(),
+        None => ChunkCoord::all().flat_map(|chunk| chunk_ennemies(tile_map, seed, chunk)).collect(),
+    };
+    for (x, y, guard) in ennemies {
+        let kind = if guard { "shrine_guard" } else { "skeleton" };
+        entities.push(ExportedEntity { kind, variant: None, x, y, hitbox: Some((x, y, ENNEMY_HITBOX_WIDTH, ENNEMY_HITBOX_HEIGHT)) });
+    }
+
+    for chunk in ChunkCoord::all() {
+        let (first_x, first_y) = chunk.first_tile();
+        for (x, y, w, h) in tile_map.collision_rects(first_x, first_y, CHUNK_TILES, CHUNK_TILES) {
+            let (left, bottom) = tile_map.tile_center(x, y);
+            let (w, h) = (w as f32 * TILE_SIZE, h as f32 * TILE_SIZE);
+            let (center_x, center_y) = (left - TILE_SIZE / 2. + w / 2., bottom - TILE_SIZE / 2. + h / 2.);
+            entities.push(ExportedEntity { kind: "terrain", variant: None, x: center_x, y: center_y, hitbox: Some((center_x, center_y, w, h)) });
+        }
+    }
+    entities
+}
+
+// Fills a hitbox of the world, the top of the image is the top of the map
+fn fill_area(image: &mut RgbImage, (x, y, w, h): (f32, f32, f32, f32), color: Rgb<u8>) {
+    let to_pixel = |value: f32| ((value + MAP_SIZE / 2.) / EXPORT_PNG_SCALE).max(0.) as u32;
+    let (min_x, max_x) = (to_pixel(x - w / 2.), to_pixel(x + w / 2.).max(to_pixel(x - w / 2.) + 1));
+    let (min_y, max_y) = (to_pixel(y - h / 2.), to_pixel(y + h / 2.).max(to_pixel(y - h / 2.) + 1));
+    for pixel_y in min_y..max_y.min(image.height()) {
+        for pixel_x in min_x..max_x.min(image.width()) {
+            image.put_pixel(pixel_x, image.height() - 1 - pixel_y, color);
+        }
+    }
+}
+
+fn render_layout(tile_map: &TileMap, entities: &[ExportedEntity]) -> RgbImage {
+    let size = (MAP_SIZE / EXPORT_PNG_SCALE) as u32;
+    let mut image = RgbImage::new(size, size);
+    for y in 0..tile_map.height() {
+        for x in 0..tile_map.width() {
+            let color = match (tile_map.get(x, y), tile_map.biome(x, y)) {
+                (TileKind::Path, _) => Rgb([194, 160, 106]),
+                (TileKind::Water, _) => Rgb([58, 110, 196]),
+                (TileKind::Cliff, _) => Rgb([90, 80, 72]),
+                (TileKind::Grass, Biome::Clearing) => Rgb([120, 186, 92]),
+                (TileKind::Grass, Biome::Forest) => Rgb([82, 150, 70]),
+                (TileKind::Grass, Biome::Graveyard) => Rgb([120, 138, 100]),
+            };
+            let (center_x, center_y) = tile_map.tile_center(x, y);
+            fill_area(&mut image, (center_x, center_y, TILE_SIZE, TILE_SIZE), color);
+        }
+    }
+    for entity in entities {
+        let color = match entity.kind {
+            "tower" => Rgb([220, 40, 40]),
+            "shrine" => Rgb([240, 220, 60]),
+            "tree" => Rgb([20, 80, 20]),
+            "bush" => Rgb([60, 120, 40]),
+            "grave" => Rgb([200, 200, 200]),
+            "skeleton" => Rgb([255, 255, 255]),
+            "shrine_guard" => Rgb([255, 140, 0]),
+            _ => continue,
+        };
+        // Bushes have no hitbox, their sprite is drawn instead
+        let area = entity.hitbox.unwrap_or((entity.x, entity.y, BUSH_WIDTH, BUSH_HEIGHT));
+        fill_area(&mut image, area, color);
+    }
+    image
+}
+
+fn write_json(path: &Path, export: &WorldExport) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(export).map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(path, content).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+// `zelda-rust export <seed> [--out <file.json>] [--png <file.png>]`, nothing is shown, the world is only planned
+pub fn export_world(seed: u64, custom_plan: Option<WorldPlan>, args: &ExportArgs) -> Result<(), String> {
+    let (tile_map, plan) = match custom_plan {
+        Some(plan) => (terrain_for_plan(seed, &plan), plan),
+        None => plan_world(seed),
+    };
+    let entities = exported_entities(seed, &tile_map, &plan);
+
+    let out = args.out.clone().unwrap_or_else(|| PathBuf::from(format!("exports/{}.json", seed)));
+    if let Some(png) = &args.png {
+        if let Some(parent) = png.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        render_layout(&tile_map, &entities).save(png).map_err(|e| format!("cannot write {}: {}", png.display(), e))?;
+        println!("layout of seed {} rendered to {}", seed, png.display());
+    }
+    let count = entities.len();
+    write_json(&out, &WorldExport { seed, size: MAP_SIZE, entities })?;
+    println!("{} entities of seed {} exported to {}", count, seed, out.display());
+    Ok(())
+}
@@ -1,12 +1,13 @@
 use bevy::{prelude::*, app::AppExit};
 
-use crate::{GameState, buttons::create_button, loading::create_restart_button};
+use crate::{GameConfig, GameState, seed::create_seed_display, buttons::create_button, loading::create_restart_button, settings::Settings};
 
 pub struct GameOverPlugin;
 
 impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::GameOver), setup_gameover)
+            .add_systems(OnEnter(GameState::Victory), setup_gameover)
             .add_systems(Update, interact_with_quit_button);
     }
 }
@@ -17,10 +18,15 @@ pub struct GameOver;
 #[derive(Component)]
 pub struct QuitButton;
 
+// Same screen when the run is lost or won, only the title changes
 fn setup_gameover(
     mut commands: Commands,
     asset_server: Res<AssetServer>, 
+    settings: Res<Settings>,
+    game_config: Res<GameConfig>,
+    state: Res<State<GameState>>,
 ) {
+    let title = if *state.get() == GameState::Victory { "Victory!" } else { "Game Over" };
     commands
         .spawn((NodeBundle {
             style: Style {
@@ -39,10 +45,20 @@ fn setup_gameover(
             ..default()
         }, GameOver))
         .with_children(|parent| {
-            create_restart_button(parent, &asset_server)
+            parent.spawn(TextBundle::from_section(settings.language.tr(title), TextStyle {
+                font_size: 60.0,
+                color: Color::WHITE,
+                ..Default::default()
+            }));
         })
         .with_children(|parent| {
-            create_button(parent, "Quit", QuitButton, &asset_server)
+            create_restart_button(parent, &asset_server, settings.language)
+        })
+        .with_children(|parent| {
+            create_seed_display(parent, &asset_server, game_config.seed, settings.language)
+        })
+        .with_children(|parent| {
+            create_button(parent, settings.language.tr("Quit"), QuitButton, &asset_server)
         });
 }
 
@@ -1,12 +1,11 @@
 use bevy::prelude::*;
-use crate::{constants::*, structures::Sanctuary, collisions::{*, self}, GameState};
+use crate::{constants::*, structures::Sanctuary, collisions::{*, self}, GameState, camera::view_half_size};
 
 pub struct GUIPlugin;
 
 impl Plugin for GUIPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnExit(GameState::Menu), setup_gui)
-            .add_systems(OnExit(GameState::Loading), setup_gui)
+        app.add_systems(OnTransition { from: GameState::Loading, to: GameState::Playing }, setup_gui)
               .add_systems(Update, (update_visibility, 
                                                       update_gui_pos,
                                                       update_display_pos).distributive_run_if(in_state(GameState::Playing)));
@@ -52,11 +51,12 @@ fn setup_gui(mut commands: Commands,
 
 fn update_gui_pos(mut query: Query<&mut GUI>,  
                   visible_sanctuary_query: Query<&Sanctuary>, 
-                  camera_pos: Query<&Transform, With<Camera>>) 
+                  camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>) 
 {
     let mut gui = query.single_mut();
-    let camera_pos = camera_pos.single();
+    let (camera_pos, camera_projection) = camera_query.single();
     let camera_pos = (camera_pos.translation.x, camera_pos.translation.y);
+    let view = view_half_size(camera_projection);
 
     let sanct_pos: Vec<&Sanctuary> = visible_sanctuary_query.iter()
     .filter(|sanctuary| sanctuary.is_visible() && !sanctuary.is_unlocked())
@@ -64,28 +64,28 @@ fn update_gui_pos(mut query: Query<&mut GUI>,
     let sanct_pos = if sanct_pos.len() > 0 {sanct_pos[0].get_pos()} else {gui.visible = false; return};
 
 
-    if is_sanct_visible(sanct_pos.0, sanct_pos.1, camera_pos.0, camera_pos.1) {
+    if is_sanct_visible(sanct_pos.0, sanct_pos.1, camera_pos.0, camera_pos.1, view) {
         gui.set_visible(false);
     } else {
         gui.set_visible(true);
     }
 
-    let (x, y) = get_gui_pos(sanct_pos.0, sanct_pos.1, camera_pos.0, camera_pos.1);
+    let (x, y) = get_gui_pos(sanct_pos.0, sanct_pos.1, camera_pos.0, camera_pos.1, view);
 
     gui.x = x;
     gui.y = y;
 }
 
-fn is_sanct_visible(sanct_x: f32, sanct_y: f32, cam_x: f32, cam_y: f32) -> bool {
+fn is_sanct_visible(sanct_x: f32, sanct_y: f32, cam_x: f32, cam_y: f32, view: Vec2) -> bool {
     collisions::are_overlapping(sanct_x, sanct_y, SANCTUARY_WIDTH, SANCTUARY_HEIGHT, 
-                             cam_x, cam_y, CAMERA_DEFAULT_SCALE * WINDOW_WIDTH, CAMERA_DEFAULT_SCALE * WINDOW_HEIGHT)
+                             cam_x, cam_y, view.x * 2., view.y * 2.)
 }
 
-fn get_gui_pos(sanct_post_x: f32, sanct_post_y: f32, cam_x: f32, cam_y: f32) -> (f32, f32) {
-    let pos_x_border = CAMERA_DEFAULT_SCALE * WINDOW_WIDTH / 2. - 10.;
-    let neg_x_border = CAMERA_DEFAULT_SCALE * -WINDOW_WIDTH / 2. + 10.;
-    let pos_y_border = CAMERA_DEFAULT_SCALE * WINDOW_HEIGHT / 2. - 10.;
-    let neg_y_border = CAMERA_DEFAULT_SCALE * -WINDOW_HEIGHT / 2. + 10.;
+fn get_gui_pos(sanct_post_x: f32, sanct_post_y: f32, cam_x: f32, cam_y: f32, view: Vec2) -> (f32, f32) {
+    let pos_x_border = view.x - 10.;
+    let neg_x_border = -view.x + 10.;
+    let pos_y_border = view.y - 10.;
+    let neg_y_border = -view.y + 10.;
 
     let ux = (sanct_post_x - cam_x) as f32;
     let uy = (sanct_post_y - cam_y) as f32;
@@ -1,14 +1,13 @@
 use bevy::prelude::*;
 
-use crate::{ennemies::Ennemy, player::Player, setup::{BackgroundObjects, Background}, structures::{Sanctuary, Tower}, gui::GUI, gameover::GameOver, GameState, LoadingState, buttons::create_button};
+use crate::{boss::{BossAreaTelegraph, BossHealthBar}, pause::Pause, ennemies::Ennemy, player::Player, setup::BackgroundObjects, structures::{Sanctuary, Tower}, gui::GUI, gameover::GameOver, GameState, buttons::create_button, settings::Language, tilemap::TileLayer};
 
 
 pub struct RestartButtonPlugin;
 
 impl Plugin for RestartButtonPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, interact_with_restart_button)
-            .add_systems(Update, load_game.run_if(in_state(GameState::Loading)));
+        app.add_systems(Update, interact_with_restart_button);
     }
 }
 
@@ -24,8 +23,9 @@ fn interact_with_restart_button(
     sanctuary_query: Query<Entity, With<Sanctuary>>,
     tower_query: Query<Entity, With<Tower>>,
     gui_query: Query<Entity, With<GUI>>,
-    bg_query: Query<Entity, With<Background>>,
-    menu_node: Query<Entity, With<Node>>,
+    menu_node: Query<Entity, Or<(With<Pause>, With<BossHealthBar>)>>,
+    telegraph_query: Query<Entity, With<BossAreaTelegraph>>,
+    tile_query: Query<Entity, With<TileLayer>>,
 ) {
     for (interaction, _) in self_button.iter_mut() {
         match *interaction {
@@ -40,8 +40,9 @@ fn interact_with_restart_button(
                                    &sanctuary_query,
                                    &tower_query,
                                    &gui_query,
-                                   &bg_query,
-                                    &menu_node);
+                                    &menu_node,
+                                   &telegraph_query,
+                                   &tile_query);
                 state.set(GameState::Loading);
                 
             }
@@ -58,8 +59,10 @@ fn despawn_everything(
     sanctuary_query: &Query<Entity, With<Sanctuary>>,
     tower_query: &Query<Entity, With<Tower>>,
     gui_query: &Query<Entity, With<GUI>>,
-    bg_query: &Query<Entity, With<Background>>,
-    button_query: &Query<Entity, With<Node>>,
+    // Only the screens of the run, the console and the debug overlay stay for the whole session
+    button_query: &Query<Entity, Or<(With<Pause>, With<BossHealthBar>)>>,
+    telegraph_query: &Query<Entity, With<BossAreaTelegraph>>,
+    tile_query: &Query<Entity, With<TileLayer>>,
 ) {
     for entity in entity_query.iter() {
         commands.entity(entity).despawn_recursive();
@@ -79,24 +82,14 @@ fn despawn_everything(
     for entity in gui_query.iter() {
         commands.entity(entity).despawn_recursive();
     }
-    for entity in bg_query.iter() {
+    for entity in button_query.iter() {
         commands.entity(entity).despawn_recursive();
     }
-    for entity in button_query.iter() {
+    for entity in telegraph_query.iter() {
         commands.entity(entity).despawn_recursive();
     }
-}
-
-fn load_game(
-    mut state: ResMut<NextState<GameState>>,
-    mut loading_state: ResMut<LoadingState>,
-    time: Res<Time>,
-) {
-    loading_state.timer.tick(time.delta());
-
-    if loading_state.timer.finished() {
-        state.set(GameState::Playing);
-        loading_state.timer.reset();
+    for entity in tile_query.iter() {
+        commands.entity(entity).despawn_recursive();
     }
 }
 
@@ -106,6 +99,7 @@ pub struct RestartButton;
 pub fn create_restart_button(
     commands: &mut ChildBuilder,
     asset_server: &Res<AssetServer>,
+    language: Language,
 ) {
-    create_button(commands, "Restart", RestartButton, &asset_server)
+    create_button(commands, language.tr("Restart"), RestartButton, &asset_server)
 }
@@ -11,14 +11,58 @@ mod pause;
 mod gameover;
 mod buttons;
 mod loading;
+mod boss;
+mod director;
+mod ai;
+mod rng;
+mod cli;
+mod replay;
+mod tick;
+mod events;
+mod audio;
+mod settings;
+mod settingsmenu;
+mod seed;
+mod camera;
+mod tilemap;
+mod worldgen;
+mod chunks;
+mod editor;
+mod mapfile;
+mod export;
+mod ysort;
+mod animation;
+mod preload;
+#[cfg(feature = "debug")]
+mod debug;
+#[cfg(feature = "debug")]
+mod console;
 
 use bevy::prelude::*;
-use bevy::window::WindowMode;
+use ai::AiPlugin;
+use boss::BossPlugin;
+use director::DirectorPlugin;
 use ennemies::EnnemyPlugin;
 use gameover::GameOverPlugin;
 use loading::RestartButtonPlugin;
 use pause::PausePlugin;
+use replay::{ReplayPlugin, InputRecorder, InputReplay, DEFAULT_REPLAY_PATH};
+use rng::RngPlugin;
 use setup::SetupPlugin;
+use tick::TickPlugin;
+use events::GameEventsPlugin;
+use audio::GameAudioPlugin;
+use settings::{Difficulty, Settings, SettingsPlugin};
+use settingsmenu::SettingsMenuPlugin;
+use seed::SeedPlugin;
+use camera::CameraPlugin;
+use tilemap::TileMapPlugin;
+use worldgen::{CustomWorld, WorldGenPlugin};
+use chunks::ChunkPlugin;
+use editor::EditorPlugin;
+use ysort::YSortPlugin;
+use animation::AnimationPlugin;
+use preload::PreloadPlugin;
 use crate::player::*;
 use crate::structures::*;
 use crate::gui::*;
@@ -31,22 +75,21 @@ pub enum GameState {
     Playing,
     Paused,
     GameOver,
+    Victory,
     Loading,
-}
-
-#[derive(Resource, Default)]
-struct LoadingState {
-    timer: Timer,
+    Settings,
+    Editor,
 }
 
 #[derive(Resource)]
 pub struct GameConfig {
     pub seed: u64,
+    pub difficulty: Difficulty,
 }
 
 impl GameConfig {
-    pub fn new(seed: u64) -> Self {
-        GameConfig { seed }
+    pub fn new(seed: u64, difficulty: Difficulty) -> Self {
+        GameConfig { seed, difficulty }
     }
 }
 
@@ -54,54 +97,108 @@ impl Default for GameConfig {
     fn default() -> Self {
         GameConfig {
             seed: DEFAULT_SEED,
+            difficulty: Difficulty::Normal,
         }
     }
 }
     
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    App::new()
-        .insert_resource(if args.len() > 1 {
-            GameConfig::new(args[1].parse().unwrap_or(DEFAULT_SEED))
-        } else {
-            GameConfig::default()
-        })
-        .insert_resource(LoadingState {
-            timer: Timer::from_seconds(1.0, TimerMode::Once), // 1 seconde
-        })
+    let cli = cli::CliArgs::parse().unwrap_or_else(|e| exit_with_error(&e));
+    let replay = cli.replay.as_ref().map(|path| InputReplay::load(path).unwrap_or_else(|e| exit_with_error(&e)));
+    let map = cli.map.as_ref().map(|path| mapfile::load_map(path).unwrap_or_else(|e| exit_with_error(&e)));
+    let given_seed = replay.as_ref().map(|replay| replay.seed)
+        .or(map.as_ref().map(|(seed, _)| *seed))
+        .or(cli.seed);
+    if let Some(export) = &cli.export {
+        // Exporting the default world by mistake would go unnoticed
+        let Some(seed) = given_seed else {
+            exit_with_error("export needs a seed or a --map");
+        };
+        if let Err(e) = export::export_world(seed, map.map(|(_, plan)| plan), export) {
+            exit_with_error(&e);
+        }
+        return;
+    }
+    let seed = given_seed.unwrap_or(DEFAULT_SEED);
+    let settings = Settings::load();
+    let difficulty = replay.as_ref().map(|replay| replay.difficulty).unwrap_or(settings.difficulty);
+    let custom_world = match &replay {
+        Some(replay) => replay.map.clone(),
+        None => map.map(|(_, plan)| plan),
+    };
+
+    let mut app = App::new();
+    if let Some(replay) = replay {
+        app.insert_resource(replay);
+    } else {
+        app.insert_resource(InputRecorder::new(cli.record.unwrap_or_else(|| DEFAULT_REPLAY_PATH.into())));
+    }
+    app
+        .insert_resource(GameConfig::new(seed, difficulty))
+        .insert_resource(CustomWorld(custom_world))
+        .insert_resource(settings)
         .add_state::<GameState>()
         .add_plugins((
             DefaultPlugins, 
+            TickPlugin,
+            GameEventsPlugin,
+            RngPlugin,
             menu::MenuPlugin, 
             PlayerPlugin, 
             SetupPlugin, 
             EnnemyPlugin, 
+            AiPlugin,
+            BossPlugin,
+            DirectorPlugin,
             StructuresPlugin, 
-            GUIPlugin, 
+            GUIPlugin,
+            YSortPlugin,
+            AnimationPlugin))
+        .add_plugins((
             PausePlugin, 
             GameOverPlugin, 
             buttons::ButtonPlugin,
-            RestartButtonPlugin))
-        .add_systems(Startup, setup_window)
-        .run();
+            RestartButtonPlugin,
+            ReplayPlugin,
+            GameAudioPlugin,
+            SettingsPlugin,
+            SettingsMenuPlugin,
+            SeedPlugin,
+            CameraPlugin,
+            TileMapPlugin,
+            WorldGenPlugin,
+            ChunkPlugin,
+            EditorPlugin,
+            PreloadPlugin))
+        .add_systems(Startup, setup_window);
+    #[cfg(feature = "debug")]
+    app.add_plugins((debug::DebugPlugin, console::ConsolePlugin));
+    app.run();
+}
+
+// Bad command line files are reported without starting the game
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
 }
 
 fn setup_window(
     mut commands: Commands,
     mut windows: Query<&mut Window>,
-
+    settings: Res<Settings>,
 ) {
     let mut window = windows.single_mut();
-    window.mode = WindowMode::BorderlessFullscreen;
+    settings.apply_to_window(&mut window);
     window.title = "Zelda".to_string();
 
-    commands.spawn(Camera2dBundle {
+    commands.spawn((Camera2dBundle {
         projection: OrthographicProjection {
             scale : CAMERA_DEFAULT_SCALE,
+            scaling_mode: camera::scaling_mode(&settings),
             far: Z_LAYER_GUI,
             ..OrthographicProjection::default()
         },
         ..Default::default()
-    });
+    }, camera::CameraController::default()));
 }
\ No newline at end of file
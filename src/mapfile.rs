@@ -0,0 +1,235 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chunks::ChunkCoord;
+use crate::collisions::Collisionable;
+use crate::constants::*;
+use crate::tilemap::DEFAULT_BACKGROUND;
+use crate::worldgen::{PlannedProp, PropKind, WorldPlan};
+
+pub const MAP_FORMAT_VERSION: u32 = 1;
+
+// Map made by hand, RON or JSON depending on the extension of the file
+#[derive(Serialize, Deserialize)]
+pub struct MapFile {
+    pub version: u32,
+    // The terrain and the paths still come from the seed
+    pub seed: u64,
+    // Reserved for maps of other sizes, only MAP_SIZE is accepted for now
+    pub size: f32,
+    pub background: String,
+    pub props: Vec<MapProp>,
+    pub towers: Vec<MapTower>,
+    pub shrines: Vec<MapShrine>,
+    pub ennemies: Vec<MapEnnemy>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MapProp {
+    pub kind: PropKind,
+    pub variant: usize,
+    pub x: f32,
+    pub y: f32,
+    // Center and size of the hitbox, the one of the kind when missing
+    #[serde(default)]
+    pub collision: Option<(f32, f32, f32, f32)>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MapTower {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MapShrine {
+    pub x: f32,
+    pub y: f32,
+    // Index of the tower revealing it, reserved for maps with several towers: it is checked, but the only tower reveals every shrine for now
+    pub tower: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MapEnnemy {
+    pub x: f32,
+    pub y: f32,
+    #[serde(default)]
+    pub guard: bool,
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "json")
+}
+
+impl MapFile {
+    pub fn from_world(seed: u64, plan: &WorldPlan) -> Self {
+        MapFile {
+            version: MAP_FORMAT_VERSION,
+            seed,
+            size: MAP_SIZE,
+            background: plan.background.clone().unwrap_or_else(|| DEFAULT_BACKGROUND.to_string()),
+            props: plan.props.iter().map(|prop| MapProp {
+                kind: prop.kind,
+                variant: prop.index,
+                x: prop.x,
+                y: prop.y,
+                collision: prop.hitbox().map(|hitbox| hitbox.get_hitbox()),
+            }).collect(),
+            towers: vec![MapTower { x: plan.tower.0, y: plan.tower.1 }],
+            shrines: plan.sanctuaries.iter().map(|&(x, y)| MapShrine { x, y, tower: 0 }).collect(),
+            ennemies: plan.ennemies.iter().flatten().map(|&(x, y, guard)| MapEnnemy { x, y, guard }).collect(),
+        }
+    }
+
+    // Seed and plan of the map, for what the game can play
+    pub fn into_world(self) -> Result<(u64, WorldPlan), String> {
+        if self.version == 0 || self.version > MAP_FORMAT_VERSION {
+            return Err(format!("map version {} is not supported (expected at most {})", self.version, MAP_FORMAT_VERSION));
+        }
+        if self.size != MAP_SIZE {
+            return Err(format!("map size {} is not supported (expected {})", self.size, MAP_SIZE));
+        }
+        if self.towers.len() != 1 {
+            return Err(format!("maps need exactly one tower, this one has {}", self.towers.len()));
+        }
+        if self.shrines.is_empty() {
+            return Err("maps need at least one shrine".to_string());
+        }
+        if let Some((index, shrine)) = self.shrines.iter().enumerate().find(|(_, shrine)| shrine.tower >= self.towers.len()) {
+            return Err(format!("shrine {} belongs to tower {} which does not exist", index, shrine.tower));
+        }
+        if let Some(prop) = self.props.iter().find(|prop| prop.variant >= PROP_VARIANTS) {
+            return Err(format!("{:?} at ({}, {}) has no variant {}", prop.kind, prop.x, prop.y, prop.variant));
+        }
+        let positions = self.props.iter().map(|prop| (prop.x, prop.y))
+            .chain(self.towers.iter().map(|tower| (tower.x, tower.y)))
+            .chain(self.shrines.iter().map(|shrine| (shrine.x, shrine.y)))
+            .chain(self.ennemies.iter().map(|ennemy| (ennemy.x, ennemy.y)));
+        for (x, y) in positions {
+            if ChunkCoord::at(x, y).is_none() {
+                return Err(format!("({}, {}) is outside of the map", x, y));
+            }
+        }
+
+        let plan = WorldPlan {
+            tower: (self.towers[0].x, self.towers[0].y),
+            sanctuaries: self.shrines.iter().map(|shrine| (shrine.x, shrine.y)).collect(),
+            props: self.props.iter().map(|prop| PlannedProp {
+                kind: prop.kind,
+                x: prop.x,
+                y: prop.y,
+                index: prop.variant,
+                collision: prop.collision.map(|(x, y, w, h)| (x - prop.x, y - prop.y, w, h)),
+            }).collect(),
+            ennemies: Some(self.ennemies.iter().map(|ennemy| (ennemy.x, ennemy.y, ennemy.guard)).collect()),
+            background: Some(self.background),
+        };
+        Ok((self.seed, plan))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let map = if is_json(path) {
+            serde_json::from_str(&content).map_err(|e| e.to_string())
+        } else {
+            ron::from_str(&content).map_err(|e| e.to_string())
+        };
+        map.map_err(|e| format!("{} is not a valid map: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = if is_json(path) {
+            serde_json::to_string_pretty(self).map_err(|e| e.to_string())?
+        } else {
+            ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(path, content).map_err(|e| e.to_string())
+    }
+}
+
+// Seed and plan of a map file, checked
+pub fn load_map(path: &Path) -> Result<(u64, WorldPlan), String> {
+    MapFile::load(path)?.into_world().map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worldgen::plan_world;
+
+    fn valid_map() -> MapFile {
+        MapFile {
+            version: MAP_FORMAT_VERSION,
+            seed: 1,
+            size: MAP_SIZE,
+            background: DEFAULT_BACKGROUND.to_string(),
+            props: vec![MapProp { kind: PropKind::Grave, variant: 0, x: 0., y: 0., collision: None }],
+            towers: vec![MapTower { x: 200., y: 200. }],
+            shrines: vec![MapShrine { x: -200., y: -200., tower: 0 }],
+            ennemies: vec![MapEnnemy { x: 100., y: -100., guard: false }],
+        }
+    }
+
+    fn error_of(edit: impl FnOnce(&mut MapFile)) -> String {
+        let mut map = valid_map();
+        edit(&mut map);
+        match map.into_world() {
+            Ok(_) => panic!("the map should be refused"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn valid_map_is_accepted() {
+        assert!(valid_map().into_world().is_ok());
+    }
+
+    #[test]
+    fn every_error_is_reported() {
+        assert!(error_of(|map| map.version = 0).contains("version"));
+        assert!(error_of(|map| map.version = MAP_FORMAT_VERSION + 1).contains("version"));
+        assert!(error_of(|map| map.size = MAP_SIZE * 2.).contains("size"));
+        assert!(error_of(|map| map.towers.clear()).contains("one tower"));
+        assert!(error_of(|map| map.towers.push(MapTower { x: 0., y: 200. })).contains("one tower"));
+        assert!(error_of(|map| map.shrines.clear()).contains("one shrine"));
+        assert!(error_of(|map| map.shrines[0].tower = 1).contains("tower 1"));
+        assert!(error_of(|map| map.props[0].variant = PROP_VARIANTS).contains("variant"));
+        assert!(error_of(|map| map.props[0].x = MAP_SIZE).contains("outside"));
+        assert!(error_of(|map| map.towers[0].y = -MAP_SIZE).contains("outside"));
+        assert!(error_of(|map| map.shrines[0].x = MAP_SIZE / 2.).contains("outside"));
+        assert!(error_of(|map| map.ennemies[0].y = MAP_SIZE / 2.).contains("outside"));
+    }
+
+    // What the editor saves is what the game plays
+    #[test]
+    fn world_survives_a_round_trip() {
+        let seed = 42;
+        let (_, plan) = plan_world(seed);
+        let content = ron::to_string(&MapFile::from_world(seed, &plan)).unwrap();
+        let (loaded_seed, loaded) = ron::from_str::<MapFile>(&content).unwrap().into_world().unwrap();
+
+        assert_eq!(loaded_seed, seed);
+        assert_eq!(loaded.tower, plan.tower);
+        assert_eq!(loaded.sanctuaries, plan.sanctuaries);
+        assert_eq!(loaded.background.as_deref(), Some(DEFAULT_BACKGROUND));
+        assert_eq!(loaded.ennemies.unwrap_or_default(), plan.ennemies.unwrap_or_default());
+        assert_eq!(loaded.props.len(), plan.props.len());
+        for (loaded, planned) in loaded.props.iter().zip(&plan.props) {
+            assert_eq!((loaded.kind, loaded.x, loaded.y, loaded.index), (planned.kind, planned.x, planned.y, planned.index));
+            let hitbox = |prop: &PlannedProp| prop.hitbox().map(|hitbox| hitbox.get_hitbox());
+            match (hitbox(loaded), hitbox(planned)) {
+                (Some((x, y, w, h)), Some((planned_x, planned_y, planned_w, planned_h))) => {
+                    assert!((x - planned_x).abs() < 1e-3 && (y - planned_y).abs() < 1e-3, "{:?} at ({}, {})", planned.kind, planned.x, planned.y);
+                    assert_eq!((w, h), (planned_w, planned_h));
+                },
+                (None, None) => {},
+                _ => panic!("{:?} at ({}, {}) lost its hitbox", planned.kind, planned.x, planned.y),
+            }
+        }
+    }
+}
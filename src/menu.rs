@@ -5,13 +5,20 @@ use crate::GameConfig;
 use crate::buttons::*;
 use crate::constants::*;
 use crate::GameState;
+use crate::settings::Settings;
+use crate::seed::{parse_seed, read_clipboard};
+use crate::tilemap::DEFAULT_BACKGROUND;
+use crate::worldgen::CustomWorld;
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup)
-            .add_systems(Update, (intteract_with_play_button, intteract_with_quit_button, start_on_press_space, start_random_seed).run_if(in_state(GameState::Menu)));
+        app.init_resource::<SeedInput>()
+            .add_systems(Startup, setup)
+            .add_systems(OnEnter(GameState::Menu), spawn_menu)
+            .add_systems(Update, (edit_seed_field, update_seed_field).chain().run_if(in_state(GameState::Menu)))
+            .add_systems(Update, (intteract_with_play_button, intteract_with_quit_button, intteract_with_settings_button, intteract_with_editor_button, start_on_press_space, start_random_seed).after(edit_seed_field).run_if(in_state(GameState::Menu)));
     }
 }
 
@@ -21,17 +28,47 @@ pub struct Menu;
 pub struct ButtonPlay;
 #[derive(Component)]
 pub struct ButtonQuit;
+#[derive(Component)]
+pub struct ButtonSettings;
+#[derive(Component)]
+pub struct ButtonEditor;
+#[derive(Component)]
+pub struct SeedField;
+
+// Seed typed on the menu, the seed given on the command line is kept while it is empty
+#[derive(Resource, Default)]
+pub struct SeedInput {
+    text: String,
+    focused: bool,
+}
+
+impl SeedInput {
+    pub fn typed_seed(&self) -> Option<u64> {
+        parse_seed(&self.text)
+    }
+}
+
+// A typed seed goes back to the world of that seed
+fn apply_seed_input(seed_input: &SeedInput, game_config: &mut GameConfig, custom_world: &mut CustomWorld) {
+    if let Some(seed) = seed_input.typed_seed() {
+        game_config.seed = seed;
+        custom_world.0 = None;
+    }
+}
 
 fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn(SpriteBundle {
-        texture: asset_server.load("Background/background.png"),
+        texture: asset_server.load(DEFAULT_BACKGROUND),
         transform: Transform {
             translation: Vec3::new(0., 0., Z_LAYER_BACKGROUND),
             ..Transform::default()
         },
         ..Default::default()
     });
+}
 
+fn spawn_menu(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>) {
+    let language = settings.language;
     commands
         .spawn((NodeBundle {
             style: Style {
@@ -50,25 +87,118 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..default()
         }, Menu))
         .with_children(|parent| {
-            create_button(parent, "Start Random Seed", RandomSeedButton, &asset_server)
+            parent
+                .spawn((ButtonBundle {
+                    style: Style {
+                        width: Val::Px(400.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    border_color: Color::GRAY.into(),
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                    ..default()
+                }, SeedField))
+                .with_children(|field| {
+                    field.spawn(TextBundle::from_section("", TextStyle {
+                        font_size: 40.0,
+                        color: Color::WHITE,
+                        ..Default::default()
+                    }));
+                });
+        })
+        .with_children(|parent| {
+            create_button(parent, language.tr("Start Random Seed"), RandomSeedButton, &asset_server)
+        })
+        .with_children(|parent| {
+            create_button(parent, language.tr("Play"), ButtonPlay, &asset_server)
+        })
+        .with_children(|parent| {
+            create_button(parent, language.tr("Settings"), ButtonSettings, &asset_server)
         })
         .with_children(|parent| {
-            create_button(parent, "Play", ButtonPlay, &asset_server)
+            create_button(parent, language.tr("Editor"), ButtonEditor, &asset_server)
         })
         .with_children(|parent| {
-            create_button(parent, "Quit", ButtonQuit, &asset_server)
+            create_button(parent, language.tr("Quit"), ButtonQuit, &asset_server)
         });
 }
 
+fn edit_seed_field(
+    mut seed_input: ResMut<SeedInput>,
+    field_query: Query<&Interaction, (Changed<Interaction>, With<SeedField>)>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+) {
+    if field_query.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        seed_input.focused = true;
+    }
+    if !seed_input.focused {
+        characters.clear();
+        return;
+    }
+    for event in characters.iter() {
+        if !event.char.is_control() && seed_input.text.chars().count() < SEED_INPUT_MAX_LENGTH {
+            seed_input.text.push(event.char);
+        }
+    }
+    let control = keyboard_input.pressed(KeyCode::ControlLeft) || keyboard_input.pressed(KeyCode::ControlRight);
+    if control && keyboard_input.just_pressed(KeyCode::V) {
+        if let Some(text) = read_clipboard() {
+            seed_input.text = text.trim().chars().take(SEED_INPUT_MAX_LENGTH).collect();
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        seed_input.text.pop();
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Escape]) {
+        seed_input.focused = false;
+    }
+}
+
+fn update_seed_field(
+    seed_input: Res<SeedInput>,
+    game_config: Res<GameConfig>,
+    settings: Res<Settings>,
+    field_query: Query<&Children, With<SeedField>>,
+    new_field_query: Query<(), Added<SeedField>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !seed_input.is_changed() && !game_config.is_changed() && new_field_query.is_empty() {
+        return;
+    }
+    for children in field_query.iter() {
+        for child in children.iter() {
+            let Ok(mut text) = text_query.get_mut(*child) else {
+                continue;
+            };
+            let section = &mut text.sections[0];
+            // Shows the seed that would be used while nothing is typed
+            if seed_input.text.is_empty() && !seed_input.focused {
+                section.value = format!("{}: {}", settings.language.tr("Seed"), game_config.seed);
+                section.style.color = Color::GRAY;
+            } else {
+                let cursor = if seed_input.focused { "_" } else { "" };
+                section.value = format!("{}: {}{}", settings.language.tr("Seed"), seed_input.text, cursor);
+                section.style.color = Color::WHITE;
+            }
+        }
+    }
+}
+
 fn intteract_with_play_button(
     mut state: ResMut<NextState<GameState>>,
     mut button_query: Query<(&Interaction, &ButtonPlay)>,
     mut commands: Commands,
-    menu_query: Query<Entity, With<Menu>>
+    menu_query: Query<Entity, With<Menu>>,
+    seed_input: Res<SeedInput>,
+    mut game_config: ResMut<GameConfig>,
+    mut custom_world: ResMut<CustomWorld>,
 ) {
     for (interaction, _) in button_query.iter_mut() {
         if *interaction == Interaction::Pressed {
-            state.set(GameState::Playing);
+            apply_seed_input(&seed_input, &mut game_config, &mut custom_world);
+            state.set(GameState::Loading);
             despawn_menu(&mut commands, &menu_query)
         }
     }
@@ -85,7 +215,39 @@ fn intteract_with_quit_button(
     }
 }
 
-fn despawn_menu(commands: &mut Commands, menu_query: &Query<Entity, With<Menu>>) {
+fn intteract_with_settings_button(
+    mut state: ResMut<NextState<GameState>>,
+    button_query: Query<&Interaction, (Changed<Interaction>, With<ButtonSettings>)>,
+    mut commands: Commands,
+    menu_query: Query<Entity, With<Menu>>
+) {
+    for interaction in button_query.iter() {
+        if *interaction == Interaction::Pressed {
+            state.set(GameState::Settings);
+            despawn_menu(&mut commands, &menu_query)
+        }
+    }
+}
+
+fn intteract_with_editor_button(
+    mut state: ResMut<NextState<GameState>>,
+    button_query: Query<&Interaction, (Changed<Interaction>, With<ButtonEditor>)>,
+    mut commands: Commands,
+    menu_query: Query<Entity, With<Menu>>,
+    seed_input: Res<SeedInput>,
+    mut game_config: ResMut<GameConfig>,
+    mut custom_world: ResMut<CustomWorld>,
+) {
+    for interaction in button_query.iter() {
+        if *interaction == Interaction::Pressed {
+            apply_seed_input(&seed_input, &mut game_config, &mut custom_world);
+            state.set(GameState::Editor);
+            despawn_menu(&mut commands, &menu_query)
+        }
+    }
+}
+
+pub fn despawn_menu(commands: &mut Commands, menu_query: &Query<Entity, With<Menu>>) {
     for entity in menu_query.iter() {
         commands.entity(entity).despawn_recursive();
     }
@@ -95,10 +257,14 @@ fn start_on_press_space(
     mut state: ResMut<NextState<GameState>>,
     keyboard_input: Res<Input<KeyCode>>,
     mut commands: Commands,
-    menu_query: Query<Entity, With<Menu>>
+    menu_query: Query<Entity, With<Menu>>,
+    seed_input: Res<SeedInput>,
+    mut game_config: ResMut<GameConfig>,
+    mut custom_world: ResMut<CustomWorld>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
-        state.set(GameState::Playing);
+    if keyboard_input.just_pressed(KeyCode::Space) && !seed_input.focused {
+        apply_seed_input(&seed_input, &mut game_config, &mut custom_world);
+        state.set(GameState::Loading);
         despawn_menu(&mut commands, &menu_query)
     }
 }
@@ -111,6 +277,7 @@ pub struct RandomSeedButton;
 fn start_random_seed(
     mut state: ResMut<NextState<GameState>>,
     mut game_config: ResMut<GameConfig>,
+    mut custom_world: ResMut<CustomWorld>,
     mut button_query: Query<(&Interaction, &RandomSeedButton)>,
     mut commands: Commands,
     menu_query: Query<Entity, With<Menu>>
@@ -119,7 +286,8 @@ fn start_random_seed(
         if *interaction == Interaction::Pressed {
             let mut rand = rand::thread_rng();
             game_config.seed = rand.gen_range(0..1000000);
-            state.set(GameState::Playing);
+            custom_world.0 = None;
+            state.set(GameState::Loading);
             despawn_menu(&mut commands, &menu_query)
         }
     }
@@ -1,6 +1,6 @@
 use bevy::{prelude::*, app::AppExit};
 
-use crate::{GameState, buttons::create_button, loading::create_restart_button};
+use crate::{GameConfig, GameState, seed::create_seed_display, buttons::create_button, loading::create_restart_button, audio::create_volume_sliders, settings::Settings};
 
 pub struct PausePlugin;
 
@@ -8,7 +8,8 @@ impl Plugin for PausePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Paused), pause_menu)
         .add_systems(Update, show_pause.run_if(in_state(GameState::Playing).or_else(in_state(GameState::Paused))))
-        .add_systems(Update, (quit, resume).run_if(in_state(GameState::Paused)));
+        .add_systems(Update, (quit, resume).run_if(in_state(GameState::Paused)))
+        .add_systems(OnExit(GameState::Paused), save_settings);
     }
 }
 
@@ -43,6 +44,8 @@ pub struct ResumeButton;
 fn pause_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>, 
+    settings: Res<Settings>,
+    game_config: Res<GameConfig>,
 ) {
     commands
         .spawn((NodeBundle {
@@ -62,13 +65,19 @@ fn pause_menu(
             ..default()
         }, Pause))
         .with_children(|parent| {
-            create_button(parent, "Resume", ResumeButton, &asset_server)
+            create_button(parent, settings.language.tr("Resume"), ResumeButton, &asset_server)
         })
         .with_children(|parent| {
-            create_restart_button(parent, &asset_server)
+            create_volume_sliders(parent, &settings)
         })
         .with_children(|parent| {
-            create_button(parent, "Quit", QuitButton, &asset_server)
+            create_restart_button(parent, &asset_server, settings.language)
+        })
+        .with_children(|parent| {
+            create_seed_display(parent, &asset_server, game_config.seed, settings.language)
+        })
+        .with_children(|parent| {
+            create_button(parent, settings.language.tr("Quit"), QuitButton, &asset_server)
         });
 }
 
@@ -99,3 +108,7 @@ fn resume(
         }
     }
 }
+
+fn save_settings(settings: Res<Settings>) {
+    settings.save();
+}
@@ -1,7 +1,6 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
-use crate::GameConfig;
 use crate::collisions;
 use crate::constants::*;
 use crate::collisions::*;
@@ -12,27 +11,34 @@ use crate::structures;
 use crate::structures::*;
 use crate::setup::*;
 use crate::GameState;
+use crate::rng::GameRng;
+use crate::tick::*;
+use crate::events::*;
+use crate::settings::GameAction;
+use crate::boss::Boss;
+use crate::ysort::YSort;
+use crate::animation::{AnimationEvent, AnimationLibrary, Animator};
+use rand::rngs::StdRng;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnExit(GameState::Menu), spawn_player)
-        .add_systems(OnExit(GameState::Loading), spawn_player)
-            .add_systems(Update, (player_move, 
-                                                    update_player_pos, 
+        app.add_systems(OnTransition { from: GameState::Loading, to: GameState::Playing }, spawn_player)
+            .add_systems(FixedUpdate, (update_player_state,
+                                                    player_move, 
                                                     player_facing_direction, 
-                                                    update_player_sprite,
+                                                    slide_out_of_collision,
+                                                    update_collision,
                                                     tower_detection,
                                                     sanctuary_detection,
-                                                    background_elements_transparency,
-                                                    update_hitbox_pos,
-                                                    // update_hitbox_visibility,
                                                     ennemy_detection,
-                                                    update_collision,
-                                                    update_player_state,
-                                                    slide_out_of_collision,
+                                                    update_player_animation,
                                                     switch_to_game_over
+                                                ).chain().in_set(TickSet::Player).run_if(in_state(GameState::Playing)))
+            .add_systems(FixedUpdate, commit_positions::<Player>.in_set(TickSet::Commit).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, (update_player_pos, 
+                                                    background_elements_transparency,
                                                 ).run_if(in_state(GameState::Playing)));
     }
 }
@@ -72,35 +78,32 @@ pub enum PlayerState {
     Dead
 }
 
+impl PlayerState {
+    fn clip(&self) -> &'static str {
+        match self {
+            PlayerState::Idle => "player_idle",
+            PlayerState::Moving => "player_walk",
+            PlayerState::Sprinting => "player_sprint",
+            PlayerState::Attacking => "player_attack",
+            PlayerState::Blocking => "player_block",
+            PlayerState::Damaged => "player_damaged",
+            PlayerState::Healing => "player_heal",
+            PlayerState::Dying => "player_dying",
+            PlayerState::Hiding => "player_hide",
+            PlayerState::Dead => "player_dead",
+        }
+    }
+}
+
 
 #[derive(Component)]
 pub struct Player {
     self_entity: EntityPatern,
     state: PlayerState,
-
-    idle_frame_counter: usize,
-    idle_frame_time: f32,
-
-    attack_frame_counter: usize,
-    attack_frame_time: f32,
-
-    sprint_frame_counter: usize,
-    sprint_frame_time: f32,
-
-    walk_frame_counter: usize,
-    walk_frame_time: f32,
-
-    healing_frame_counter: usize,
-    healing_frame_time: f32,
     healing_duration_elapsed: f32,
 
-    damaged_frame_counter: usize,
-    damaged_frame_time: f32,
-    damaged_duration_elapsed: f32,
-
-    dying_frame_counter: usize,
-    dying_frame_time: f32,
-    dying_duration_elapsed: f32,
+    god: bool,
+    noclip: bool,
 }
 
 impl Player {
@@ -108,13 +111,8 @@ impl Player {
         Self { 
                self_entity: EntityPatern::new(0., 0., PLAYER_HITBOX_WIDTH * 0.8, PLAYER_HITBOX_HEIGHT * 0.8, PLAYER_HEALTH),
                state: PlayerState::Idle, 
-               idle_frame_counter: 0, idle_frame_time: 0., 
-               attack_frame_counter: 0, attack_frame_time: 0., 
-               sprint_frame_counter: 0, sprint_frame_time: 0., 
-               walk_frame_counter: 0, walk_frame_time: 0.,
-               healing_frame_counter: 0, healing_frame_time: 0., healing_duration_elapsed: 0.,
-               damaged_frame_counter: 0, damaged_frame_time: 0., damaged_duration_elapsed: 0.,
-                dying_frame_counter: 0, dying_frame_time: 0., dying_duration_elapsed: 0.,
+               healing_duration_elapsed: 0.,
+               god: false, noclip: false,
              }
     }
 
@@ -150,7 +148,11 @@ impl Player {
         self.state == PlayerState::Attacking
     }
 
-    fn is_blocking(&self) -> bool {
+    pub fn health(&self) -> i32 {
+        self.self_entity.health()
+    }
+
+    pub fn is_blocking(&self) -> bool {
         self.state == PlayerState::Blocking
     }
 
@@ -176,10 +178,30 @@ impl Player {
 
     fn heal(&mut self) {
         self.self_entity.add_health(SANCTUARY_HEALING);
-        println!("Player health now at {}", self.self_entity.health());
         self.state = PlayerState::Healing;
 
     }
+
+    #[cfg(feature = "debug")]
+    pub fn restore_health(&mut self) {
+        let missing = PLAYER_HEALTH - self.self_entity.health();
+        self.self_entity.add_health(missing);
+        if self.is_dying() || self.is_dead() {
+            self.state = PlayerState::Idle;
+        }
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn toggle_god(&mut self) -> bool {
+        self.god = !self.god;
+        self.god
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn toggle_noclip(&mut self) -> bool {
+        self.noclip = !self.noclip;
+        self.noclip
+    }
 }
 
 impl Collisionable for Player {
@@ -193,21 +215,18 @@ impl Collisionable for Player {
 }
 
 impl EntityBehavior for Player {
-    fn attack(&mut self, target: &mut dyn EntityBehavior) -> bool {
-        return target.get_attacked(PLAYER_DAMAGE);
+    fn attack(&mut self, target: &mut dyn EntityBehavior, rng: &mut StdRng) -> bool {
+        return target.get_attacked(PLAYER_DAMAGE, rng);
     }
 
-    fn get_attacked(&mut self, damage: i32) -> bool {
-        if !self.is_aggroable() {
+    fn get_attacked(&mut self, damage: i32, _rng: &mut StdRng) -> bool {
+        if !self.is_aggroable() || self.god {
             return false;
         }
         if self.is_blocking() {
-            println!("Player blocked the attack");
             return false;
         }
         self.take_damage(damage);
-        println!("Player took {} damage", damage);
-        println!("Player health now at {}", self.self_entity.health());
         true
     }
 
@@ -254,57 +273,36 @@ impl EntityBehavior for Player {
 }
 
 
-#[derive(Component)]
-pub struct HitBox;
-
 fn spawn_player(mut commands: Commands, 
-    asset_server: Res<AssetServer>, 
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,) 
+    animation_library: Res<AnimationLibrary>,) 
     {
 
-    let texture_handle = asset_server.load("player.png");
-    let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(PLAYER_SPRITE_SIZE, PLAYER_SPRITE_SIZE), 8, 9, Some(Vec2::new(0., 0.)), Some(Vec2::new(0., 1.)));
-    let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
     let player: Player = Player::new();
     let (x, y) = player.get_pos();
     let collisioncomponent = CollisionComponent::new(x, y, PLAYER_HITBOX_WIDTH, PLAYER_HITBOX_HEIGHT);
 
     let attack_delay = AttackDelay::new(PLAYER_ATTACK_DELAY);
-    
-    let hitbox = player.get_hitbox();
 
     commands.spawn(SpriteSheetBundle {
-            texture_atlas: texture_atlas_handle.clone(),
+            texture_atlas: animation_library.atlas("player_idle"),
             transform: Transform {
-                translation: Vec3::new(0., 0., 1.),
-                scale: Vec3::new(PLAYER_SPRITE_SCALE, PLAYER_SPRITE_SCALE, Z_LAYER_PLAYER),
+                translation: Vec3::new(0., 0., Z_LAYER_WORLD),
+                scale: Vec3::new(PLAYER_SPRITE_SCALE, PLAYER_SPRITE_SCALE, 1.),
                 ..Transform::default()
             },
             sprite: TextureAtlasSprite::new(0),
             ..Default::default()
         })
+        .insert(SimPosition::new(x, y))
         .insert(player)
         .insert(attack_delay)
-        .insert(collisioncomponent);
-
-    commands.spawn(SpriteBundle {
-        transform: Transform {
-            translation: Vec3::new(0., 0., Z_LAYER_GUI),
-            ..Transform::default()
-        },
-        sprite: Sprite {
-            custom_size: Some(Vec2::new(hitbox.2, hitbox.3)),
-            color: Color::rgb(0.0, 0.0, 1.0),
-            ..Default::default()
-        },
-        visibility: Visibility::Hidden,
-        ..Default::default()
-    }).insert(HitBox);
+        .insert(collisioncomponent)
+        .insert(YSort::new(PLAYER_HITBOX_HEIGHT / 2.))
+        .insert(Animator::new("player_idle"));
 }
 
 fn update_player_state(
-    keyboard_input: Res<Input<KeyCode>>,
+    keyboard_input: Res<TickInput>,
     mut query: Query<&mut Player>,
 ) {
     let mut player = query.single_mut();
@@ -315,17 +313,17 @@ fn update_player_state(
 
     // CLASSE PAR ORDRE DIMPORTANCE
 
-    if keyboard_input.pressed(KeyCode::ShiftLeft){
+    if keyboard_input.pressed(GameAction::Sprint){
         player.state = PlayerState::Sprinting;
     }
     
-    else if keyboard_input.pressed(KeyCode::Up) || keyboard_input.pressed(KeyCode::Down) || keyboard_input.pressed(KeyCode::Left) || keyboard_input.pressed(KeyCode::Right) {
+    else if keyboard_input.pressed(GameAction::Up) || keyboard_input.pressed(GameAction::Down) || keyboard_input.pressed(GameAction::Left) || keyboard_input.pressed(GameAction::Right) {
         player.state = PlayerState::Moving;
     } 
-    else if keyboard_input.pressed(KeyCode::E) {
+    else if keyboard_input.pressed(GameAction::Block) {
         player.state = PlayerState::Blocking;
     }
-    else if keyboard_input.pressed(KeyCode::A) {
+    else if keyboard_input.pressed(GameAction::Hide) {
         player.state = PlayerState::Hiding;
     }
     else {
@@ -334,7 +332,7 @@ fn update_player_state(
 }
 
 fn player_move(
-    keyboard_input: Res<Input<KeyCode>>,
+    keyboard_input: Res<TickInput>,
     mut player_query: Query<&mut Player>,
     collisionable_query: Query<&CollisionComponent, Without<Player>>,
 ) {
@@ -346,11 +344,11 @@ fn player_move(
         return;
     }
 
-    if keyboard_input.pressed(KeyCode::ShiftLeft) {
-        player_speed = PLAYER_SPRINT_SPEED;
+    if keyboard_input.pressed(GameAction::Sprint) {
+        player_speed = PLAYER_SPRINT_SPEED * FIXED_TIMESTEP;
 
     } else {
-        player_speed = PLAYER_NORMAL_SPEED;
+        player_speed = PLAYER_NORMAL_SPEED * FIXED_TIMESTEP;
     }
     let left_boundary = -((MAP_SIZE / 2.0) - (PLAYER_HITBOX_WIDTH / 2.));
     let right_boundary = -left_boundary;
@@ -360,11 +358,11 @@ fn player_move(
     let actual_x = player.x();
     let actual_y = player.y();
 
-    let mut new_x = if keyboard_input.pressed(KeyCode::Left) { player.x() - player_speed }
-                     else if keyboard_input.pressed(KeyCode::Right) { player.x() + player_speed }
+    let mut new_x = if keyboard_input.pressed(GameAction::Left) { player.x() - player_speed }
+                     else if keyboard_input.pressed(GameAction::Right) { player.x() + player_speed }
                      else { player.x() };
 
-    for collidable in collisionable_query.iter() {
+    for collidable in collisionable_query.iter().filter(|_| !player.noclip) {
         if player.would_collide(new_x, player.y(), collidable){ 
             new_x = actual_x;
         }
@@ -376,11 +374,11 @@ fn player_move(
         new_x = actual_x;
     }
 
-    let mut new_y = if keyboard_input.pressed(KeyCode::Down) { player.y() - player_speed }
-                     else if keyboard_input.pressed(KeyCode::Up) { player.y() + player_speed }
+    let mut new_y = if keyboard_input.pressed(GameAction::Down) { player.y() - player_speed }
+                     else if keyboard_input.pressed(GameAction::Up) { player.y() + player_speed }
                      else { player.y() };
 
-    for collidable in collisionable_query.iter() {
+    for collidable in collisionable_query.iter().filter(|_| !player.noclip) {
         if player.would_collide(player.x(), new_y, collidable){ 
             new_y = actual_y;
         }
@@ -397,7 +395,7 @@ fn player_move(
 
 
 fn player_facing_direction(
-    keyboard_input: Res<Input<KeyCode>>,
+    keyboard_input: Res<TickInput>,
     mut query: Query<&mut Player>,
 ) {
     let mut player = query.single_mut();
@@ -405,142 +403,52 @@ fn player_facing_direction(
     if !player.can_change_facing_direction() {
         return;
     }
-    if keyboard_input.pressed(KeyCode::Left) && keyboard_input.pressed(KeyCode::Up) {
+    if keyboard_input.pressed(GameAction::Left) && keyboard_input.pressed(GameAction::Up) {
         player.set_facing_direction(FacingDirection::TopLeft)
     }
-    else if keyboard_input.pressed(KeyCode::Left) && keyboard_input.pressed(KeyCode::Down) {
+    else if keyboard_input.pressed(GameAction::Left) && keyboard_input.pressed(GameAction::Down) {
         player.set_facing_direction(FacingDirection::BottomLeft)
     }
-    else if keyboard_input.pressed(KeyCode::Right) && keyboard_input.pressed(KeyCode::Up) {
+    else if keyboard_input.pressed(GameAction::Right) && keyboard_input.pressed(GameAction::Up) {
         player.set_facing_direction(FacingDirection::TopRight)
     }
-    else if keyboard_input.pressed(KeyCode::Right) && keyboard_input.pressed(KeyCode::Down) {
+    else if keyboard_input.pressed(GameAction::Right) && keyboard_input.pressed(GameAction::Down) {
         player.set_facing_direction(FacingDirection::BottomRight)
     }
-    else if keyboard_input.pressed(KeyCode::Left) {
+    else if keyboard_input.pressed(GameAction::Left) {
         player.set_facing_direction(FacingDirection::Left)
     }
-    else if keyboard_input.pressed(KeyCode::Right) {
+    else if keyboard_input.pressed(GameAction::Right) {
         player.set_facing_direction(FacingDirection::Right)
     }
-    else if keyboard_input.pressed(KeyCode::Up) {
+    else if keyboard_input.pressed(GameAction::Up) {
         player.set_facing_direction(FacingDirection::Up)
     }
-    else if keyboard_input.pressed(KeyCode::Down) {
+    else if keyboard_input.pressed(GameAction::Down) {
         player.set_facing_direction(FacingDirection::Down)
     }
 }
 
 
-fn update_player_sprite(
-    mut query: Query<(&mut Player, &mut TextureAtlasSprite)>,
-    time: Res<Time>,
+fn update_player_animation(
+    mut query: Query<(&mut Player, &mut Animator)>,
 ) {
-    let (mut player, mut texture) = query.single_mut();
-    match player.state {
-        PlayerState::Idle => {
-            player.idle_frame_time += time.delta_seconds();
-
-            if player.idle_frame_time >= 0.4 {
-                player.idle_frame_counter = (player.idle_frame_counter + 1) % 4;
-                player.idle_frame_time = 0.0; 
-            }
-            texture.index = match player.idle_frame_counter {
-                0 => 0,
-                1 => 1,
-                2 => 8,
-                3 => 9,
-                _ => 0,
-            };
-        },
-        PlayerState::Blocking => texture.index = 2,
-        PlayerState::Moving => {
-            player.walk_frame_time += time.delta_seconds();
-
-            if player.walk_frame_time >= 0.2 {
-                player.walk_frame_counter = (player.walk_frame_counter + 1) % 4;
-                player.walk_frame_time = 0.0; 
-            }
-            texture.index = 16 + player.walk_frame_counter;
-        },
-        PlayerState::Sprinting => {
-            player.sprint_frame_time += time.delta_seconds();
-
-            if player.sprint_frame_time >= 0.1 {
-                player.sprint_frame_counter = (player.sprint_frame_counter + 1) % 8;
-                player.sprint_frame_time = 0.0; 
-            }
-            texture.index = 24 + player.sprint_frame_counter;
-
-        },
-        PlayerState::Attacking => {
-            player.attack_frame_time += time.delta_seconds();
-
-            // Changer de frame toutes les 0.1 secondes (ou selon votre choix)
-            if player.attack_frame_time >= 0.1 {
-                player.attack_frame_counter = player.attack_frame_counter + 1;
-                player.attack_frame_time = 0.0; // Réinitialiser le temps écoulé
-            }
-
-            // Mettre à jour l'index de la texture
-            texture.index = 64 + player.attack_frame_counter;
-            if player.attack_frame_counter >= 7 {
-                player.state = PlayerState::Idle;
-                player.attack_frame_counter = 0;
-            }
-        },
-
-        PlayerState::Dying => {
-            player.dying_frame_time += time.delta_seconds();
-            player.dying_duration_elapsed += time.delta_seconds();
-
-            if player.dying_frame_time >= 1./8. {
-                player.dying_frame_counter = (player.dying_frame_counter + 1) % 8;
-                player.dying_frame_time = 0.0; 
-            }
-            texture.index = 56 + player.dying_frame_counter;
-            if player.dying_duration_elapsed >= 1. {
-                player.state = PlayerState::Dead;
-                player.dying_duration_elapsed = 0.;
-            }
-        },
-        
-        PlayerState::Dead => {
-            texture.index = 63;
-        },
-        PlayerState::Healing => {
-            player.healing_frame_time += time.delta_seconds();
-            player.healing_duration_elapsed += time.delta_seconds();
-
-            if player.healing_frame_time >= 0.4 {
-                player.healing_frame_counter = (player.healing_frame_counter + 1) % 3;
-                player.healing_frame_time = 0.0; 
-            }
-            texture.index = 3 + player.healing_frame_counter;
-            if player.healing_duration_elapsed >= 3. {
-                player.state = PlayerState::Idle;
-                player.healing_duration_elapsed = 0.;
-            }
-            
-        },
-        PlayerState::Damaged => {
-            player.damaged_frame_time += time.delta_seconds();
-            player.damaged_duration_elapsed += time.delta_seconds();
-
-            if player.damaged_frame_time >= 0.15 {
-                player.damaged_frame_counter = (player.damaged_frame_counter + 1) % 3;
-                player.damaged_frame_time = 0.0; 
-            }
-            texture.index = 48 + player.damaged_frame_counter;
-            if player.damaged_duration_elapsed >= 0.15*3. {
-                player.state = PlayerState::Idle;
-                player.damaged_duration_elapsed = 0.;
-            }
+    let (mut player, mut animator) = query.single_mut();
+    if animator.fired(player.state.clip(), AnimationEvent::End) {
+        match player.state {
+            PlayerState::Attacking | PlayerState::Damaged => player.state = PlayerState::Idle,
+            PlayerState::Dying => player.state = PlayerState::Dead,
+            _ => (),
+        }
+    }
+    if player.state == PlayerState::Healing {
+        player.healing_duration_elapsed += FIXED_TIMESTEP;
+        if player.healing_duration_elapsed >= 3. {
+            player.state = PlayerState::Idle;
+            player.healing_duration_elapsed = 0.;
         }
-        PlayerState::Hiding => {
-            texture.index = 35;
-        },
     }
+    animator.play(player.state.clip());
 }
 
 fn switch_to_game_over(
@@ -554,14 +462,10 @@ fn switch_to_game_over(
 }
 
 fn update_player_pos(
-    mut query: Query<(&mut Player, &mut Transform)>,
+    mut query: Query<(&Player, &mut Transform)>,
 ) {
 
     let (player, mut sprite) = query.single_mut();
-    let x = player.x();
-    let y = player.y();
-    sprite.translation.x = x;
-    sprite.translation.y = y;
     if let Some(facing_direction) = &player.facing_direction() {
         match facing_direction {
             FacingDirection::Left => sprite.scale.x = -PLAYER_SPRITE_SCALE,
@@ -575,30 +479,7 @@ fn update_player_pos(
     }
 }
 
-fn update_hitbox_pos(
-    player_query: Query<&Player>,
-    mut hitbox_query: Query<&mut Transform, With<HitBox>>,
-) {
-    let player = player_query.single();
-    for mut transform in hitbox_query.iter_mut() {
-        transform.translation.x = player.x();
-        transform.translation.y = player.y();
-    }
-}
 
-// fn update_hitbox_visibility(
-//     keyboard_input: Res<Input<KeyCode>>,
-//     mut hitbox_query: Query<&mut Visibility, With<HitBox>>,
-// ) {
-//     for mut visibility in hitbox_query.iter_mut() {
-//         if keyboard_input.just_pressed(KeyCode::L) {
-//             *visibility = Visibility::Visible;
-//         }
-//         if keyboard_input.just_pressed(KeyCode::K) {
-//             *visibility = Visibility::Hidden;
-//         }
-//     }
-// }
 
 fn update_collision(
     mut query: Query<(&mut CollisionComponent, &Player)>,
@@ -647,17 +528,19 @@ fn can_interact_with(
 
 fn tower_detection(
     mut player_query: Query<&mut Player>,
-    tower_query: Query<&Tower>,
-    keyboard_input: Res<Input<KeyCode>>,
+    mut tower_query: Query<(Entity, &mut Tower)>,
+    keyboard_input: Res<TickInput>,
     query_sanctuary: Query<&mut Sanctuary>,
-    game_config: Res<GameConfig>,
-    nextstate : ResMut<NextState<GameState>>
+    mut game_rng: ResMut<GameRng>,
+    nextstate : ResMut<NextState<GameState>>,
+    mut tower_events: EventWriter<TowerActivated>,
 ) {
     let player = player_query.single_mut();
-    for tower in tower_query.iter() {
-        if can_interact_with(&player, &InteractionType::Tower, player.x(), player.y() + 1., Some(tower), None, None) {
-            if keyboard_input.just_pressed(KeyCode::Space) {
-                structures::show_one_sanctuary(query_sanctuary, game_config, nextstate);
+    for (entity, mut tower) in tower_query.iter_mut() {
+        if can_interact_with(&player, &InteractionType::Tower, player.x(), player.y() + 1., Some(&tower), None, None) {
+            if keyboard_input.just_pressed(GameAction::Attack) {
+                let activation = structures::show_one_sanctuary(&mut tower, query_sanctuary, &mut game_rng, nextstate);
+                tower_events.send(TowerActivated { entity, activation });
                 break;
             }
         }
@@ -666,18 +549,22 @@ fn tower_detection(
 
 fn sanctuary_detection(
     mut player_query: Query<&mut Player>,
-    mut sanctuary_query: Query<&mut Sanctuary>,
-    keyboard_input: Res<Input<KeyCode>>,
+    mut sanctuary_query: Query<(Entity, &mut Sanctuary)>,
+    keyboard_input: Res<TickInput>,
+    mut shrine_events: EventWriter<ShrineUnlocked>,
+    mut healed_events: EventWriter<PlayerHealed>,
 ) {
     let mut player = player_query.single_mut();
-    for mut sanctuary in sanctuary_query.iter_mut() {
+    for (entity, mut sanctuary) in sanctuary_query.iter_mut() {
         if can_interact_with(&player, &InteractionType::Sanctuary, player.x(), player.y() + 1., None, Some(&sanctuary), None) ||
             can_interact_with(&player, &InteractionType::Sanctuary, player.x(), player.y() - 1., None, Some(&sanctuary), None) ||
             can_interact_with(&player, &InteractionType::Sanctuary, player.x() + 1., player.y(), None, Some(&sanctuary), None) ||
             can_interact_with(&player, &InteractionType::Sanctuary, player.x() - 1., player.y(), None, Some(&sanctuary), None) {
-            if keyboard_input.just_pressed(KeyCode::Space) {
+            if keyboard_input.just_pressed(GameAction::Attack) {
                 if sanctuary.unlock() {  
+                    shrine_events.send(ShrineUnlocked { entity, position: sanctuary.get_pos() });
                     player.heal();
+                    healed_events.send(PlayerHealed { health: player.health() });
                 }
                 break;
             }
@@ -685,43 +572,60 @@ fn sanctuary_detection(
     }
 }
 
+fn in_attack_range(player: &Player, ennemy: &Ennemy) -> bool {
+    can_interact_with(player, &InteractionType::Ennemy, player.x(), player.y() + PLAYER_ATTACK_RANGE, None, None, Some(ennemy)) ||
+        can_interact_with(player, &InteractionType::Ennemy, player.x(), player.y() - PLAYER_ATTACK_RANGE, None, None, Some(ennemy)) ||
+        can_interact_with(player, &InteractionType::Ennemy, player.x() + PLAYER_ATTACK_RANGE, player.y(), None, None, Some(ennemy)) ||
+        can_interact_with(player, &InteractionType::Ennemy, player.x() - PLAYER_ATTACK_RANGE, player.y(), None, None, Some(ennemy))
+}
+
+// The swing starts on the key press, the blow lands on the Hit frame of player_attack
 fn ennemy_detection(
-    mut player_query: Query<(&mut Player, &mut AttackDelay)>,
-    mut ennemy_query: Query<&mut Ennemy>,
-    keyboard_input: Res<Input<KeyCode>>,
-    time: Res<Time>,
+    mut player_query: Query<(&mut Player, &mut AttackDelay, &Animator)>,
+    mut ennemy_query: Query<(Entity, &mut Ennemy, Option<&Boss>)>,
+    keyboard_input: Res<TickInput>,
+    mut game_rng: ResMut<GameRng>,
+    mut damaged_events: EventWriter<EnemyDamaged>,
+    mut blocked_events: EventWriter<EnemyBlocked>,
+    mut killed_events: EventWriter<EnemyKilled>,
+    mut attacked_events: EventWriter<PlayerAttacked>,
 ) {
-    let (mut player, mut attack_delay) = player_query.single_mut();
+    let (mut player, mut attack_delay, animator) = player_query.single_mut();
     
-    attack_delay.timer.tick(time.delta());
+    attack_delay.timer.tick(tick_duration());
 
-
-    if attack_delay.timer.finished() {
-        if player.is_attacking() {
-            player.state = PlayerState::Idle;
-        }
-        for mut ennemy in ennemy_query.iter_mut() {
-            if can_interact_with(&player, &InteractionType::Ennemy, player.x(), player.y() + PLAYER_ATTACK_RANGE, None, None, Some(&ennemy)) ||
-                can_interact_with(&player, &InteractionType::Ennemy, player.x(), player.y() - PLAYER_ATTACK_RANGE, None, None, Some(&ennemy)) ||
-                can_interact_with(&player, &InteractionType::Ennemy, player.x() + PLAYER_ATTACK_RANGE, player.y(), None, None, Some(&ennemy)) ||
-                can_interact_with(&player, &InteractionType::Ennemy, player.x() - PLAYER_ATTACK_RANGE, player.y(), None, None, Some(&ennemy)) {
-                if keyboard_input.just_pressed(KeyCode::Space) {
-                    let actual_ennemy: &mut ennemies::Ennemy = &mut ennemy;
-                    player.attack(actual_ennemy);
-                    player.state = PlayerState::Attacking;
-                    attack_delay.timer.reset();
-                    break;
-                }
+    if player.is_attacking() && animator.fired(PlayerState::Attacking.clip(), AnimationEvent::Hit) {
+        for (entity, mut ennemy, boss) in ennemy_query.iter_mut() {
+            if !in_attack_range(&player, &ennemy) {
+                continue;
+            }
+            let actual_ennemy: &mut ennemies::Ennemy = &mut ennemy;
+            let health_before = actual_ennemy.health();
+            if !player.attack(actual_ennemy, &mut game_rng.combat) {
+                blocked_events.send(EnemyBlocked { entity });
+            } else if actual_ennemy.is_dying() {
+                killed_events.send(EnemyKilled { entity, position: actual_ennemy.get_pos(), boss: boss.is_some() });
+            } else {
+                damaged_events.send(EnemyDamaged { entity, damage: health_before - actual_ennemy.health(), health: actual_ennemy.health() });
             }
+            break;
         }
+        return;
+    }
+
+    if attack_delay.timer.finished() && keyboard_input.just_pressed(GameAction::Attack)
+        && ennemy_query.iter().any(|(_, ennemy, _)| in_attack_range(&player, ennemy)) {
+        attacked_events.send(PlayerAttacked);
+        player.state = PlayerState::Attacking;
+        attack_delay.timer.reset();
     }
 }
 
 pub fn background_elements_transparency(
-    mut player_query: Query<&mut Player>,
-    mut background_objects: Query<(&mut TextureAtlasSprite, &Transform, &BackgroundObjects)>,
+    mut player_query: Query<(&mut Player, &Transform)>,
+    mut background_objects: Query<(&mut TextureAtlasSprite, &Transform, &BackgroundObjects), Without<Player>>,
 ) {
-    let player = player_query.single_mut();
+    let (player, player_transform) = player_query.single_mut();
     for (mut sprite, transform, obj) in background_objects.iter_mut() {
         let (x, y) = player.get_pos();
         let (bg_obj_width, bg_obj_height) = (
@@ -737,7 +641,9 @@ pub fn background_elements_transparency(
             _ => 0.,
         });
 
-        if collisions::are_overlapping(x, y, PLAYER_HITBOX_WIDTH, PLAYER_HITBOX_HEIGHT, transform.translation.x, transform.translation.y, bg_obj_width, bg_obj_height) {
+        // Only what is drawn in front of the player hides them
+        let in_front = transform.translation.z > player_transform.translation.z;
+        if in_front && collisions::are_overlapping(x, y, PLAYER_HITBOX_WIDTH, PLAYER_HITBOX_HEIGHT, transform.translation.x, transform.translation.y, bg_obj_width, bg_obj_height) {
             sprite.color.set_a(0.50);
         } else {
             sprite.color.set_a(1.0);
@@ -750,6 +656,9 @@ fn slide_out_of_collision(
     mut collisionable_query: Query<&mut CollisionComponent, Without<Player>>,
 ) {
     let mut player = player_query.single_mut();
+    if player.noclip {
+        return;
+    }
     let (orig_x, orig_y) = player.get_pos();
     
     // Vérifier si le joueur est actuellement en collision avec quelque chose
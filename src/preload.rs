@@ -0,0 +1,195 @@
+use bevy::app::AppExit;
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::GameState;
+use crate::constants::*;
+use crate::settings::Settings;
+use crate::worldgen::CustomWorld;
+
+// Every texture, sound and font the game needs, see the comment at the top of the file
+const MANIFEST: &str = include_str!("../assets/manifest.ron");
+
+pub struct PreloadPlugin;
+
+impl Plugin for PreloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PreloadedAssets>()
+            .add_systems(OnEnter(GameState::Loading), (preload_map_assets, spawn_loading_screen))
+            .add_systems(Update, update_loading_screen.run_if(in_state(GameState::Loading)))
+            .add_systems(OnExit(GameState::Loading), despawn_loading_screen);
+    }
+}
+
+#[derive(Deserialize)]
+struct AssetManifest {
+    #[serde(default)]
+    textures: Vec<String>,
+    #[serde(default)]
+    sounds: Vec<String>,
+    #[serde(default)]
+    fonts: Vec<String>,
+}
+
+// Handles on everything of the manifest, loaded from startup and kept for every run
+#[derive(Resource)]
+pub struct PreloadedAssets {
+    handles: Vec<(String, HandleUntyped)>,
+    // What the map of the run needs on top of the manifest, its background
+    map_handles: Vec<(String, HandleUntyped)>,
+}
+
+impl FromWorld for PreloadedAssets {
+    fn from_world(world: &mut World) -> Self {
+        let manifest: AssetManifest = ron::from_str(MANIFEST).unwrap_or_else(|e| panic!("assets/manifest.ron is not valid: {}", e));
+        let asset_server = world.resource::<AssetServer>();
+        let handles = manifest.textures.into_iter()
+            .chain(manifest.sounds)
+            .chain(manifest.fonts)
+            .map(|path| {
+                let handle = asset_server.load_untyped(path.as_str());
+                (path, handle)
+            })
+            .collect();
+        PreloadedAssets { handles, map_handles: Vec::new() }
+    }
+}
+
+impl PreloadedAssets {
+    fn all(&self) -> impl Iterator<Item = &(String, HandleUntyped)> {
+        self.handles.iter().chain(self.map_handles.iter())
+    }
+
+    // How many are loaded, out of all of them
+    fn progress(&self, asset_server: &AssetServer) -> (usize, usize) {
+        let loaded = self.all().filter(|(_, handle)| asset_server.get_load_state(handle) == LoadState::Loaded).count();
+        (loaded, self.handles.len() + self.map_handles.len())
+    }
+
+    fn missing(&self, asset_server: &AssetServer) -> Vec<&str> {
+        self.all()
+            .filter(|(_, handle)| asset_server.get_load_state(handle) == LoadState::Failed)
+            .map(|(path, _)| path.as_str())
+            .collect()
+    }
+}
+
+// A map file can name any background, it is only known once the run is chosen
+fn preload_map_assets(
+    asset_server: Res<AssetServer>,
+    custom_world: Res<CustomWorld>,
+    mut preloaded: ResMut<PreloadedAssets>,
+) {
+    let background = custom_world.0.as_ref().and_then(|plan| plan.background.clone());
+    preloaded.map_handles = background.into_iter()
+        .map(|path| {
+            let handle = asset_server.load_untyped(path.as_str());
+            (path, handle)
+        })
+        .collect();
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingText;
+
+#[derive(Component)]
+struct LoadingFill;
+
+fn spawn_loading_screen(
+    mut commands: Commands,
+    settings: Res<Settings>,
+) {
+    commands
+        .spawn((NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(20.0),
+                ..default()
+            },
+            background_color: Color::BLACK.into(),
+            ..default()
+        }, LoadingScreen))
+        .with_children(|parent| {
+            parent.spawn((TextBundle::from_section(settings.language.tr("Loading"), TextStyle {
+                font_size: 40.0,
+                color: Color::WHITE,
+                ..default()
+            }), LoadingText));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(LOADING_BAR_WIDTH),
+                        height: Val::Px(LOADING_BAR_HEIGHT),
+                        ..default()
+                    },
+                    background_color: Color::rgba(1.0, 1.0, 1.0, 0.2).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((NodeBundle {
+                        style: Style {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: Color::WHITE.into(),
+                        ..default()
+                    }, LoadingFill));
+                });
+        });
+}
+
+// The run starts once everything is loaded, a missing asset is shown instead and the game can only be left
+fn update_loading_screen(
+    asset_server: Res<AssetServer>,
+    preloaded: Res<PreloadedAssets>,
+    settings: Res<Settings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+    mut fill_query: Query<&mut Style, With<LoadingFill>>,
+    mut text_query: Query<&mut Text, With<LoadingText>>,
+    mut reported: Local<bool>,
+) {
+    let missing = preloaded.missing(&asset_server);
+    if !missing.is_empty() {
+        if !*reported {
+            *reported = true;
+            error!(target: "zelda::assets", "cannot load {}", missing.join(", "));
+            let language = settings.language;
+            for mut text in text_query.iter_mut() {
+                text.sections[0].value = format!("{}\n{}\n\n{}", language.tr("Missing assets:"), missing.join("\n"), language.tr("Press Esc to quit"));
+                text.sections[0].style.color = Color::rgb(1.0, 0.3, 0.3);
+            }
+        }
+        if keyboard_input.just_pressed(KeyCode::Escape) {
+            exit.send(AppExit);
+        }
+        return;
+    }
+
+    let (loaded, total) = preloaded.progress(&asset_server);
+    for mut style in fill_query.iter_mut() {
+        style.width = Val::Percent(if total == 0 { 100. } else { loaded as f32 / total as f32 * 100. });
+    }
+    if loaded == total {
+        state.set(GameState::Playing);
+    }
+}
+
+fn despawn_loading_screen(
+    mut commands: Commands,
+    screen_query: Query<Entity, With<LoadingScreen>>,
+) {
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
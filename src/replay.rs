@@ -0,0 +1,192 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+use crate::GameConfig;
+use crate::GameState;
+use crate::mapfile::MapFile;
+use crate::menu::{Menu, despawn_menu};
+use crate::settings::{Difficulty, GameAction};
+use crate::tick::*;
+use crate::worldgen::{CustomWorld, WorldPlan};
+
+pub const REPLAY_HEADER: &str = "zelda-replay 4";
+pub const DEFAULT_REPLAY_PATH: &str = "replays/last.replay";
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnTransition { from: GameState::Loading, to: GameState::Playing }, start_recording.run_if(resource_exists::<InputRecorder>()))
+            .add_systems(FixedUpdate, (feed_replay.run_if(resource_exists::<InputReplay>()),
+                                       record_inputs.run_if(resource_exists::<InputRecorder>()))
+                                       .chain()
+                                       .after(begin_tick)
+                                       .in_set(TickSet::Input)
+                                       .run_if(in_state(GameState::Playing)))
+            .add_systems(Update, start_replay.run_if(resource_exists::<InputReplay>().and_then(in_state(GameState::Menu))))
+            .add_systems(OnEnter(GameState::GameOver), save_recording.run_if(resource_exists::<InputRecorder>()))
+            .add_systems(OnEnter(GameState::Victory), save_recording.run_if(resource_exists::<InputRecorder>()))
+            .add_systems(Last, save_recording_on_exit.run_if(resource_exists::<InputRecorder>()));
+    }
+}
+
+// Actions held during one tick, then actions pressed since the previous tick
+type RecordedTick = (Vec<GameAction>, Vec<GameAction>);
+
+#[derive(Resource)]
+pub struct InputRecorder {
+    path: PathBuf,
+    seed: u64,
+    difficulty: Difficulty,
+    // Played instead of the world of the seed (--map or the editor)
+    map: Option<WorldPlan>,
+    ticks: Vec<RecordedTick>,
+}
+
+impl InputRecorder {
+    pub fn new(path: PathBuf) -> Self {
+        InputRecorder { path, seed: 0, difficulty: Difficulty::Normal, map: None, ticks: Vec::new() }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut content = format!("{}\nseed {}\ndifficulty {:?}\n", REPLAY_HEADER, self.seed, self.difficulty);
+        // The whole map is kept on a single line, the file it came from may have changed since
+        if let Some(plan) = &self.map {
+            let map = ron::to_string(&MapFile::from_world(self.seed, plan)).map_err(std::io::Error::other)?;
+            content.push_str(&format!("map {}\n", map));
+        }
+        for (pressed, just_pressed) in self.ticks.iter() {
+            content.push_str("tick");
+            for action in pressed {
+                content.push_str(&format!(" {:?}", action));
+            }
+            content.push_str(" /");
+            for action in just_pressed {
+                content.push_str(&format!(" {:?}", action));
+            }
+            content.push('\n');
+        }
+        fs::write(&self.path, content)
+    }
+}
+
+#[derive(Resource)]
+pub struct InputReplay {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub map: Option<WorldPlan>,
+    ticks: Vec<RecordedTick>,
+    cursor: usize,
+}
+
+impl InputReplay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let mut lines = content.lines();
+        if lines.next() != Some(REPLAY_HEADER) {
+            return Err(format!("{} is not a replay file (expected {})", path.display(), REPLAY_HEADER));
+        }
+        let seed = lines.next()
+            .and_then(|line| line.strip_prefix("seed "))
+            .and_then(|seed| seed.trim().parse().ok())
+            .ok_or_else(|| format!("{} has no seed", path.display()))?;
+        let difficulty = lines.next()
+            .and_then(|line| line.strip_prefix("difficulty "))
+            .and_then(|difficulty| Difficulty::from_name(difficulty.trim()))
+            .ok_or_else(|| format!("{} has no difficulty", path.display()))?;
+
+        let mut lines = lines.peekable();
+        let map = match lines.peek().and_then(|line| line.strip_prefix("map ")) {
+            Some(map) => {
+                let map: MapFile = ron::from_str(map).map_err(|e| format!("{} has an invalid map: {}", path.display(), e))?;
+                lines.next();
+                Some(map.into_world().map_err(|e| format!("{}: {}", path.display(), e))?.1)
+            },
+            None => None,
+        };
+
+        let mut ticks = Vec::new();
+        for line in lines {
+            let Some((pressed, just_pressed)) = line.strip_prefix("tick").and_then(|rest| rest.split_once('/')) else {
+                return Err(format!("unexpected line in replay: {}", line));
+            };
+            ticks.push((parse_actions(pressed)?, parse_actions(just_pressed)?));
+        }
+        Ok(InputReplay { seed, difficulty, map, ticks, cursor: 0 })
+    }
+}
+
+fn parse_actions(names: &str) -> Result<Vec<GameAction>, String> {
+    names.split_whitespace()
+        .map(|name| GameAction::from_name(name)
+            .ok_or_else(|| format!("unknown action in replay: {}", name)))
+        .collect()
+}
+
+// A new run starts when leaving the menu or the loading screen
+fn start_recording(
+    mut recorder: ResMut<InputRecorder>,
+    game_config: Res<GameConfig>,
+    custom_world: Res<CustomWorld>,
+) {
+    recorder.seed = game_config.seed;
+    recorder.difficulty = game_config.difficulty;
+    recorder.map = custom_world.0.clone();
+    recorder.ticks.clear();
+}
+
+fn record_inputs(
+    mut recorder: ResMut<InputRecorder>,
+    tick_input: Res<TickInput>,
+) {
+    recorder.ticks.push((tick_input.pressed_actions(), tick_input.just_pressed_actions()));
+}
+
+fn save_recording(recorder: Res<InputRecorder>) {
+    if recorder.ticks.is_empty() {
+        return;
+    }
+    match recorder.save() {
+        Ok(()) => info!(target: "zelda::replay", "replay saved to {}", recorder.path.display()),
+        Err(e) => warn!(target: "zelda::replay", "could not save replay: {}", e),
+    }
+}
+
+fn save_recording_on_exit(
+    recorder: Res<InputRecorder>,
+    mut exit_events: EventReader<AppExit>,
+) {
+    if exit_events.iter().next().is_some() {
+        save_recording(recorder);
+    }
+}
+
+fn start_replay(
+    mut state: ResMut<NextState<GameState>>,
+    mut commands: Commands,
+    menu_query: Query<Entity, With<Menu>>,
+) {
+    state.set(GameState::Loading);
+    despawn_menu(&mut commands, &menu_query);
+}
+
+fn feed_replay(
+    mut commands: Commands,
+    mut replay: ResMut<InputReplay>,
+    mut tick_input: ResMut<TickInput>,
+) {
+    let Some((pressed, just_pressed)) = replay.ticks.get(replay.cursor) else {
+        info!(target: "zelda::replay", "replay finished after {} ticks", replay.cursor);
+        tick_input.set(&[], &[]);
+        commands.remove_resource::<InputReplay>();
+        return;
+    };
+    tick_input.set(pressed, just_pressed);
+    replay.cursor += 1;
+}
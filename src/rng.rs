@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::GameConfig;
+use crate::GameState;
+use crate::constants::*;
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameRng::new(DEFAULT_SEED))
+            .add_systems(OnTransition { from: GameState::Loading, to: GameState::Playing }, reseed_game_rng);
+    }
+}
+
+// One stream per area of the game, so that drawing more for the AI does not change the spawns or the hits.
+// combat is shared by the player, enemy and boss systems, which run in a fixed order in TickSet
+#[derive(Resource)]
+pub struct GameRng {
+    pub ai: StdRng,
+    pub combat: StdRng,
+    pub director: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            ai: StdRng::seed_from_u64(seed.wrapping_add(OFFSET_RNG_AI)),
+            combat: StdRng::seed_from_u64(seed.wrapping_add(OFFSET_RNG_COMBAT)),
+            director: StdRng::seed_from_u64(seed.wrapping_add(OFFSET_DIRECTOR)),
+        }
+    }
+}
+
+fn reseed_game_rng(
+    mut game_rng: ResMut<GameRng>,
+    game_config: Res<GameConfig>,
+) {
+    *game_rng = GameRng::new(game_config.seed);
+}
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+use crate::GameConfig;
+use crate::buttons::create_button;
+use crate::settings::Language;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+pub struct SeedPlugin;
+
+impl Plugin for SeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, copy_seed);
+    }
+}
+
+// A number is used as is, any other text is hashed so that "my run" is always the same world
+pub fn parse_seed(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    Some(text.parse().unwrap_or_else(|_| fnv1a(text)))
+}
+
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+pub fn read_clipboard() -> Option<String> {
+    match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+        Ok(text) => Some(text),
+        Err(e) => {
+            warn!(target: "zelda::seed", "could not read the clipboard: {}", e);
+            None
+        },
+    }
+}
+
+#[derive(Component)]
+pub struct CopySeedButton;
+
+// Seed of the current run and a button to copy it, for the pause and game over screens
+pub fn create_seed_display(
+    commands: &mut ChildBuilder,
+    asset_server: &Res<AssetServer>,
+    seed: u64,
+    language: Language,
+) {
+    commands.spawn(TextBundle::from_section(format!("{}: {}", language.tr("Seed"), seed), TextStyle {
+        font_size: 40.0,
+        color: Color::WHITE,
+        ..Default::default()
+    }));
+    create_button(commands, language.tr("Copy Seed"), CopySeedButton, asset_server);
+}
+
+fn copy_seed(
+    button_query: Query<&Interaction, (Changed<Interaction>, With<CopySeedButton>)>,
+    game_config: Res<GameConfig>,
+) {
+    for interaction in button_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let result = arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(game_config.seed.to_string()));
+        match result {
+            Ok(()) => info!(target: "zelda::seed", "seed {} copied to the clipboard", game_config.seed),
+            Err(e) => warn!(target: "zelda::seed", "could not copy the seed: {}", e),
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy::window::{PresentMode, WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::audio::AudioSettings;
+use crate::constants::*;
+
+pub const SETTINGS_FILE_NAME: &str = "settings.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_window_settings.run_if(resource_changed::<Settings>()));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum GameAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Sprint,
+    Block,
+    Hide,
+    Attack,
+}
+
+impl GameAction {
+    pub const ALL: [GameAction; 8] = [
+        GameAction::Up,
+        GameAction::Down,
+        GameAction::Left,
+        GameAction::Right,
+        GameAction::Sprint,
+        GameAction::Block,
+        GameAction::Hide,
+        GameAction::Attack,
+    ];
+
+    pub fn default_key(&self) -> KeyCode {
+        match self {
+            GameAction::Up => KeyCode::Up,
+            GameAction::Down => KeyCode::Down,
+            GameAction::Left => KeyCode::Left,
+            GameAction::Right => KeyCode::Right,
+            GameAction::Sprint => KeyCode::ShiftLeft,
+            GameAction::Block => KeyCode::E,
+            GameAction::Hide => KeyCode::A,
+            GameAction::Attack => KeyCode::Space,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GameAction> {
+        GameAction::ALL.iter().copied().find(|action| format!("{:?}", action) == name)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyBindings {
+    bindings: Vec<(GameAction, KeyCode)>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            bindings: GameAction::ALL.iter().map(|action| (*action, action.default_key())).collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn key(&self, action: GameAction) -> KeyCode {
+        self.bindings.iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, key)| *key)
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    pub fn bind(&mut self, action: GameAction, key: KeyCode) {
+        self.bindings.retain(|(bound, _)| *bound != action);
+        self.bindings.push((action, key));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum WindowModeSetting {
+    Windowed,
+    BorderlessFullscreen,
+    Fullscreen,
+}
+
+impl WindowModeSetting {
+    pub fn next(&self) -> Self {
+        match self {
+            WindowModeSetting::Windowed => WindowModeSetting::BorderlessFullscreen,
+            WindowModeSetting::BorderlessFullscreen => WindowModeSetting::Fullscreen,
+            WindowModeSetting::Fullscreen => WindowModeSetting::Windowed,
+        }
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        match self {
+            WindowModeSetting::Windowed => WindowMode::Windowed,
+            WindowModeSetting::BorderlessFullscreen => WindowMode::BorderlessFullscreen,
+            WindowModeSetting::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Language {
+    English,
+    French,
+}
+
+impl Language {
+    pub fn next(&self) -> Self {
+        match self {
+            Language::English => Language::French,
+            Language::French => Language::English,
+        }
+    }
+
+    pub fn tr<'a>(&self, text: &'a str) -> &'a str {
+        if *self == Language::English {
+            return text;
+        }
+        match text {
+            "Play" => "Jouer",
+            "Start Random Seed" => "Graine aléatoire",
+            "Settings" => "Paramètres",
+            "Quit" => "Quitter",
+            "Resume" => "Reprendre",
+            "Restart" => "Recommencer",
+            "Back" => "Retour",
+            "Window" => "Fenêtre",
+            "Resolution" => "Résolution",
+            "VSync" => "Synchro verticale",
+            "Fixed View" => "Vue fixe",
+            "Language" => "Langue",
+            "Difficulty" => "Difficulté",
+            "Master" => "Général",
+            "Music" => "Musique",
+            "SFX" => "Effets",
+            "On" => "Activée",
+            "Off" => "Désactivée",
+            "Windowed" => "Fenêtré",
+            "BorderlessFullscreen" => "Plein écran fenêtré",
+            "Fullscreen" => "Plein écran",
+            "English" => "English",
+            "French" => "Français",
+            "Easy" => "Facile",
+            "Normal" => "Normale",
+            "Hard" => "Difficile",
+            "Up" => "Haut",
+            "Down" => "Bas",
+            "Left" => "Gauche",
+            "Right" => "Droite",
+            "Sprint" => "Courir",
+            "Block" => "Parer",
+            "Hide" => "Se cacher",
+            "Attack" => "Attaquer",
+            "Press a key" => "Appuyez sur une touche",
+            "Seed" => "Graine",
+            "Copy Seed" => "Copier la graine",
+            "Editor" => "Éditeur",
+            "Loading" => "Chargement",
+            "Missing assets:" => "Ressources manquantes :",
+            "Press Esc to quit" => "Appuyez sur Échap pour quitter",
+            "Game Over" => "Partie terminée",
+            "Victory!" => "Victoire !",
+            _ => text,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn next(&self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn ennemy_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => DIFFICULTY_EASY_MULTIPLIER,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => DIFFICULTY_HARD_MULTIPLIER,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard].into_iter().find(|difficulty| format!("{:?}", difficulty) == name)
+    }
+}
+
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub window_mode: WindowModeSetting,
+    pub resolution: (u32, u32),
+    pub vsync: bool,
+    pub fixed_view: bool,
+    pub audio: AudioSettings,
+    pub key_bindings: KeyBindings,
+    pub language: Language,
+    pub difficulty: Difficulty,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            window_mode: WindowModeSetting::BorderlessFullscreen,
+            resolution: (WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32),
+            vsync: true,
+            fixed_view: false,
+            audio: AudioSettings::default(),
+            key_bindings: KeyBindings::default(),
+            language: Language::English,
+            difficulty: Difficulty::Normal,
+        }
+    }
+}
+
+impl Settings {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("zelda-rust").join(SETTINGS_FILE_NAME))
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = Settings::path() else {
+            return Settings::default();
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Settings::default();
+        };
+        ron::from_str(&content).unwrap_or_else(|e| {
+            warn!(target: "zelda::settings", "ignoring invalid settings file {}: {}", path.display(), e);
+            Settings::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Settings::path() else {
+            warn!(target: "zelda::settings", "no config directory, settings are not saved");
+            return;
+        };
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                fs::write(&path, content).map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(()) => info!(target: "zelda::settings", "settings saved to {}", path.display()),
+            Err(e) => warn!(target: "zelda::settings", "could not save settings: {}", e),
+        }
+    }
+
+    pub fn next_resolution(&self) -> (u32, u32) {
+        let index = RESOLUTIONS.iter().position(|resolution| *resolution == self.resolution);
+        match index {
+            Some(i) => RESOLUTIONS[(i + 1) % RESOLUTIONS.len()],
+            None => RESOLUTIONS[0],
+        }
+    }
+
+    fn window_fields(&self) -> (WindowModeSetting, (u32, u32), bool) {
+        (self.window_mode, self.resolution, self.vsync)
+    }
+
+    pub fn apply_to_window(&self, window: &mut Window) {
+        window.mode = self.window_mode.window_mode();
+        window.resolution.set(self.resolution.0 as f32, self.resolution.1 as f32);
+        window.present_mode = if self.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync };
+    }
+}
+
+// Only when the window fields change, volumes or key bindings must not undo a manual resize
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window>,
+    mut applied: Local<Option<(WindowModeSetting, (u32, u32), bool)>>,
+) {
+    let fields = settings.window_fields();
+    if *applied == Some(fields) {
+        return;
+    }
+    *applied = Some(fields);
+    for mut window in windows.iter_mut() {
+        settings.apply_to_window(&mut window);
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+
+use crate::GameConfig;
+use crate::GameState;
+use crate::audio::create_volume_sliders;
+use crate::buttons::create_button;
+use crate::replay::InputReplay;
+use crate::settings::{GameAction, Settings};
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_systems(OnEnter(GameState::Settings), spawn_settings_menu)
+            .add_systems(OnExit(GameState::Settings), close_settings_menu)
+            .add_systems(Update, (capture_key_binding,
+                                  interact_with_settings_buttons,
+                                  update_settings_labels).chain().run_if(in_state(GameState::Settings)));
+    }
+}
+
+#[derive(Component)]
+pub struct SettingsMenu;
+
+#[derive(Component, Clone, Copy, PartialEq)]
+enum SettingsButton {
+    WindowMode,
+    Resolution,
+    VSync,
+    FixedView,
+    Language,
+    Difficulty,
+    Binding(GameAction),
+    Back,
+}
+
+// Action waiting for its new key
+#[derive(Resource, Default)]
+struct Rebinding(Option<GameAction>);
+
+fn label(button: SettingsButton, settings: &Settings, rebinding: &Rebinding) -> String {
+    let language = settings.language;
+    match button {
+        SettingsButton::WindowMode => format!("{}: {}", language.tr("Window"), language.tr(&format!("{:?}", settings.window_mode))),
+        SettingsButton::Resolution => format!("{}: {}x{}", language.tr("Resolution"), settings.resolution.0, settings.resolution.1),
+        SettingsButton::VSync => format!("{}: {}", language.tr("VSync"), language.tr(if settings.vsync { "On" } else { "Off" })),
+        SettingsButton::FixedView => format!("{}: {}", language.tr("Fixed View"), language.tr(if settings.fixed_view { "On" } else { "Off" })),
+        SettingsButton::Language => format!("{}: {}", language.tr("Language"), language.tr(&format!("{:?}", settings.language))),
+        SettingsButton::Difficulty => format!("{}: {}", language.tr("Difficulty"), language.tr(&format!("{:?}", settings.difficulty))),
+        SettingsButton::Binding(action) => {
+            let name = language.tr(&format!("{:?}", action)).to_string();
+            if rebinding.0 == Some(action) {
+                format!("{}: {}", name, language.tr("Press a key"))
+            } else {
+                format!("{}: {:?}", name, settings.key_bindings.key(action))
+            }
+        },
+        SettingsButton::Back => language.tr("Back").to_string(),
+    }
+}
+
+fn column() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(10.0),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn build_settings_menu(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    settings: &Settings,
+) {
+    let rebinding = Rebinding::default();
+    commands
+        .spawn((NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(40.0),
+                ..default()
+            },
+            ..default()
+        }, SettingsMenu))
+        .with_children(|parent| {
+            parent.spawn(column()).with_children(|parent| {
+                for button in [SettingsButton::WindowMode,
+                               SettingsButton::Resolution,
+                               SettingsButton::VSync,
+                               SettingsButton::FixedView,
+                               SettingsButton::Language,
+                               SettingsButton::Difficulty] {
+                    create_button(parent, &label(button, settings, &rebinding), button, asset_server);
+                }
+                create_volume_sliders(parent, settings);
+                create_button(parent, &label(SettingsButton::Back, settings, &rebinding), SettingsButton::Back, asset_server);
+            });
+            parent.spawn(column()).with_children(|parent| {
+                for action in GameAction::ALL {
+                    let button = SettingsButton::Binding(action);
+                    create_button(parent, &label(button, settings, &rebinding), button, asset_server);
+                }
+            });
+        });
+}
+
+fn spawn_settings_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+) {
+    build_settings_menu(&mut commands, &asset_server, &settings);
+}
+
+fn close_settings_menu(
+    mut commands: Commands,
+    menu_query: Query<Entity, With<SettingsMenu>>,
+    settings: Res<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut game_config: ResMut<GameConfig>,
+    replay: Option<Res<InputReplay>>,
+) {
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    rebinding.0 = None;
+    // A replay is always played with the difficulty it was recorded with
+    if replay.is_none() {
+        game_config.difficulty = settings.difficulty;
+    }
+    settings.save();
+}
+
+fn capture_key_binding(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let Some(key) = keyboard_input.get_just_pressed().next().copied() else {
+        return;
+    };
+    match rebinding.0.take() {
+        Some(_) if key == KeyCode::Escape => (),
+        Some(action) => settings.key_bindings.bind(action, key),
+        None if key == KeyCode::Escape => state.set(GameState::Menu),
+        None => (),
+    }
+}
+
+fn interact_with_settings_buttons(
+    mut commands: Commands,
+    button_query: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+    menu_query: Query<Entity, With<SettingsMenu>>,
+    mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut state: ResMut<NextState<GameState>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (interaction, button) in button_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match *button {
+            SettingsButton::WindowMode => settings.window_mode = settings.window_mode.next(),
+            SettingsButton::Resolution => settings.resolution = settings.next_resolution(),
+            SettingsButton::VSync => settings.vsync = !settings.vsync,
+            SettingsButton::FixedView => settings.fixed_view = !settings.fixed_view,
+            SettingsButton::Difficulty => settings.difficulty = settings.difficulty.next(),
+            SettingsButton::Binding(action) => rebinding.0 = Some(action),
+            SettingsButton::Back => state.set(GameState::Menu),
+            SettingsButton::Language => {
+                settings.language = settings.language.next();
+                // Every text of the screen changes, simpler to build it again
+                for entity in menu_query.iter() {
+                    commands.entity(entity).despawn_recursive();
+                }
+                build_settings_menu(&mut commands, &asset_server, &settings);
+            },
+        }
+    }
+}
+
+fn update_settings_labels(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    button_query: Query<(&SettingsButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !settings.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+    for (button, children) in button_query.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = label(*button, &settings, &rebinding);
+            }
+        }
+    }
+}
@@ -1,19 +1,20 @@
 use bevy::prelude::*;
 use rand::prelude::*;
-use crate::GameConfig;
+use crate::rng::GameRng;
 use crate::GameState;
 use crate::constants::*;
 use crate::collisions::*;
+use crate::events::TowerActivation;
+use crate::worldgen::{WorldPlan, generate_world};
+use crate::ysort::YSort;
 
 pub struct StructuresPlugin;
 
 impl Plugin for StructuresPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnExit(GameState::Menu), setup_structures)
-            .add_systems(OnExit(GameState::Loading), setup_structures)
+        app.add_systems(OnTransition { from: GameState::Loading, to: GameState::Playing }, setup_structures.after(generate_world))
             .add_systems(Update, (update_structures_pos,  
                                                     update_visibility, 
-                                                    // change_visibility_with_keybinding, 
                                                     update_collision_component,
                                                     update_sanctuary_color,
                                                 
@@ -21,16 +22,33 @@ impl Plugin for StructuresPlugin {
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum BossProgress {
+    Dormant,
+    Summoned,
+    Fighting,
+    Defeated,
+}
+
 #[derive(Component, Clone)]
 pub struct Tower {
     x: f32,
     y: f32,
+    boss_progress: BossProgress,
 }
 
 
 impl Tower {
     pub fn new(x: f32, y: f32) -> Self {
-        Tower { x, y}
+        Tower { x, y, boss_progress: BossProgress::Dormant }
+    }
+
+    pub fn boss_progress(&self) -> BossProgress {
+        self.boss_progress
+    }
+
+    pub fn set_boss_progress(&mut self, boss_progress: BossProgress) {
+        self.boss_progress = boss_progress;
     }
 
 }
@@ -57,9 +75,7 @@ impl Sanctuary {
     pub fn new(x: f32, y: f32) -> Self {
         Sanctuary { x, y, visibility: true, unlocked: false }
     }
-    pub fn new_random_position(seed: u64) -> Self {
-        let mut rng = StdRng::seed_from_u64(seed);
-
+    pub fn new_random_position(rng: &mut StdRng) -> Self {
         let max_value_x = MAP_SIZE / 2. - SANCTUARY_WIDTH / 2.;
         let max_value_y = MAP_SIZE / 2. - SANCTUARY_HEIGHT / 2.;
 
@@ -70,10 +86,8 @@ impl Sanctuary {
 
     pub fn unlock(&mut self) -> bool {
         if self.unlocked {
-            println!("Sanctuaire déjà débloqué");
             return false;
         }
-        println!("Sanctuaire débloqué");
         self.unlocked = true;
         true
     }
@@ -85,6 +99,11 @@ impl Sanctuary {
     pub fn is_unlocked(&self) -> bool {
         self.unlocked
     }
+
+    #[cfg(feature = "debug")]
+    pub fn reveal(&mut self) {
+        self.visibility = true;
+    }
 }
 
 impl Collisionable for Sanctuary {
@@ -101,46 +120,34 @@ impl Collisionable for Sanctuary {
     }
 }
 
-fn does_collide_with_existing(sanctuary: &Sanctuary, query: &Query<&CollisionComponent>, added_sanctuaries: &[CollisionComponent]) -> bool {
-    query.iter().any(|existing| sanctuary.would_collide_with(existing))
-        || added_sanctuaries.iter().any(|added| sanctuary.would_collide_with(added))
-}
-
 fn setup_sanctuary(
     commands: &mut Commands, 
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-    collision_query: &Query<&CollisionComponent>,
-    game_config: Res<GameConfig>,
+    plan: &WorldPlan,
 ) {
     // Load the sanctuary texture
     let texture_handle = asset_server.load("sanctuary.png");
     let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(SANCTUARY_WIDTH, SANCTUARY_HEIGHT), 2, 1, Some(Vec2 { x: 1., y: 0. }), Some(Vec2::new(0., 0.))); // Assuming two textures side by side.
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
 
-    let mut added_sanctuaries = Vec::new();
-
-    for _ in 0..SANCTUARY_NB {
-        if let Some(mut sanctuary) = (0..10)
-            .map(|_| Sanctuary::new_random_position(game_config.seed + OFFSET_SANCTUARY + added_sanctuaries.len() as u64))
-            .find(|sanct| !does_collide_with_existing(sanct, &collision_query, &added_sanctuaries)) {
-
-            let collision_component = CollisionComponent::new_from_component(&sanctuary);
-            added_sanctuaries.push(collision_component.clone());
+    for &(x, y) in plan.sanctuaries.iter() {
+        let mut sanctuary = Sanctuary::new(x, y);
+        let collision_component = CollisionComponent::new_from_component(&sanctuary);
 
-            sanctuary.visibility = false;
+        sanctuary.visibility = false;
 
-            commands.spawn(SpriteSheetBundle {
-                texture_atlas: texture_atlas_handle.clone(),
-                transform: Transform::from_xyz(sanctuary.x as f32, sanctuary.y as f32, Z_LAYER_STRUCTURES),
-                sprite: TextureAtlasSprite { index: 0, ..Default::default() }, // Use the first texture (red one)
-                ..Default::default()
-            })
-            .insert(collision_component)
-            .insert(sanctuary);
-            println!("Sanctuaire ajouté");
-        }
+        debug!(target: "zelda::world", "shrine placed at ({:.0}, {:.0})", sanctuary.x, sanctuary.y);
 
+        commands.spawn(SpriteSheetBundle {
+            texture_atlas: texture_atlas_handle.clone(),
+            transform: Transform::from_xyz(sanctuary.x as f32, sanctuary.y as f32, Z_LAYER_WORLD),
+            sprite: TextureAtlasSprite { index: 0, ..Default::default() }, // Use the first texture (red one)
+            ..Default::default()
+        })
+        .insert(collision_component)
+        .insert(sanctuary)
+        .insert(YSort::new(SANCTUARY_HEIGHT / 2.));
     }
 }
 
@@ -148,23 +155,18 @@ fn setup_sanctuary(
 pub fn setup_structures(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    collision_query: Query<&CollisionComponent>,
     texture_atlases: ResMut<Assets<TextureAtlas>>,
-    game_config: Res<GameConfig>,
+    plan: Res<WorldPlan>,
 ) {
     let tower_texture_handle = asset_server.load("tower.png");
 
-    let mut rng = StdRng::seed_from_u64(game_config.seed + OFFSET_TOWER);
-    let x = rng.gen_range(-MAP_SIZE / 2. + TOWER_WIDTH..MAP_SIZE / 2. - TOWER_WIDTH);
-    let y = rng.gen_range(-MAP_SIZE / 2. + TOWER_HEIGHT + PLAYER_HITBOX_HEIGHT..MAP_SIZE / 2. - TOWER_HEIGHT);
-
-    let tower = Tower::new(x, y);
-    setup_sanctuary(&mut commands, asset_server, texture_atlases, &collision_query, game_config);
+    let tower = Tower::new(plan.tower.0, plan.tower.1);
+    setup_sanctuary(&mut commands, asset_server, texture_atlases, &plan);
 
     // Setup tower
     let collisioncomponent = CollisionComponent::new_from_component(&tower);
     commands.spawn(SpriteBundle {
-        transform: Transform::from_xyz(0.0, 0.0, Z_LAYER_STRUCTURES),
+        transform: Transform::from_xyz(0.0, 0.0, Z_LAYER_WORLD),
         sprite: Sprite {
             custom_size: Some(Vec2::new(TOWER_WIDTH, TOWER_HEIGHT)),
             ..Default::default()
@@ -173,7 +175,8 @@ pub fn setup_structures(
         ..Default::default()
     })
     .insert(tower)
-    .insert(collisioncomponent);
+    .insert(collisioncomponent)
+    .insert(YSort::new(TOWER_HEIGHT / 2.));
 
     
 
@@ -191,40 +194,34 @@ fn update_visibility(mut query: Query<(&mut Visibility, &Sanctuary)>) {
     }
 }
 
-// fn change_visibility_with_keybinding(
-//     keyboard_input: Res<Input<KeyCode>>,
-//     mut query: Query<&mut Sanctuary>,
-// ) {
-//     if keyboard_input.just_pressed(KeyCode::V) {
-//         for mut sanctuary in query.iter_mut() {
-//             sanctuary.visibility = !sanctuary.visibility;
-//         }
-//     }
-// }
-
 fn update_collision_component(mut query: Query<(&mut CollisionComponent, &Sanctuary)>) {
     for (mut collision_component, sanctuary) in query.iter_mut() {
         collision_component.update_hitbox(sanctuary);
     }
 }
 
-pub fn show_one_sanctuary(mut query: Query<&mut Sanctuary>, game_config: Res<GameConfig>, mut nextstate: ResMut<NextState<GameState>>) {
+pub fn show_one_sanctuary(tower: &mut Tower, mut query: Query<&mut Sanctuary>, rng: &mut GameRng, mut nextstate: ResMut<NextState<GameState>>) -> TowerActivation {
     if are_all_visible_sanctuaries_unlocked(&query) {
 
-        let mut rng = StdRng::seed_from_u64(game_config.seed);
         let mut sanctuaries: Vec<_> = query.iter_mut().filter(|sanctuary| !sanctuary.visibility).collect();
         let len = sanctuaries.len();
 
         if len > 0 {
-            let sanctuary = &mut sanctuaries[rng.gen_range(0..len)];
+            let sanctuary = &mut sanctuaries[rng.combat.gen_range(0..len)];
             sanctuary.visibility = true;
+            if len == 1 && tower.boss_progress == BossProgress::Dormant {
+                tower.boss_progress = BossProgress::Summoned;
+            }
+            TowerActivation::ShrineRevealed
+        } else if tower.boss_progress == BossProgress::Defeated {
+            nextstate.set(GameState::Victory);
+            TowerActivation::Freed
         } else {
-            println!("Pas de sanctuaire trouvé");
-            nextstate.set(GameState::GameOver);
+            TowerActivation::BossAlive
         }
     }
     else {
-        println!("Tous les sanctuaires ne sont pas débloqués");
+        TowerActivation::ShrinesLocked
     }
 
 }
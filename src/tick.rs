@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+use crate::GameState;
+use crate::constants::*;
+use crate::entitypattern::EntityBehavior;
+use crate::settings::{GameAction, Settings};
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TickSet {
+    Input,
+    Player,
+    Ai,
+    Ennemies,
+    Boss,
+    Director,
+    Animation,
+    Commit,
+}
+
+pub struct TickPlugin;
+
+impl Plugin for TickPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FixedTime::new_from_secs(FIXED_TIMESTEP))
+            .init_resource::<TickInput>()
+            .init_resource::<InputLatch>()
+            .configure_sets(FixedUpdate, (TickSet::Input,
+                                          TickSet::Player,
+                                          TickSet::Ai,
+                                          TickSet::Ennemies,
+                                          TickSet::Boss,
+                                          TickSet::Director,
+                                          TickSet::Animation,
+                                          TickSet::Commit).chain())
+            .add_systems(PreUpdate, latch_inputs.after(InputSystem).run_if(in_state(GameState::Playing)))
+            .add_systems(FixedUpdate, begin_tick.in_set(TickSet::Input).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, interpolate_positions.run_if(in_state(GameState::Playing)));
+    }
+}
+
+pub fn tick_duration() -> Duration {
+    Duration::from_secs_f32(FIXED_TIMESTEP)
+}
+
+// Actions as seen by one simulation tick: keys pressed between two ticks are never missed nor counted twice
+#[derive(Resource, Default)]
+pub struct TickInput {
+    pressed: HashSet<GameAction>,
+    just_pressed: HashSet<GameAction>,
+}
+
+impl TickInput {
+    pub fn pressed(&self, action: GameAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: GameAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn pressed_actions(&self) -> Vec<GameAction> {
+        GameAction::ALL.iter().copied().filter(|action| self.pressed(*action)).collect()
+    }
+
+    pub fn just_pressed_actions(&self) -> Vec<GameAction> {
+        GameAction::ALL.iter().copied().filter(|action| self.just_pressed(*action)).collect()
+    }
+
+    pub fn set(&mut self, pressed: &[GameAction], just_pressed: &[GameAction]) {
+        self.pressed = pressed.iter().copied().collect();
+        self.just_pressed = just_pressed.iter().copied().collect();
+    }
+}
+
+#[derive(Resource, Default)]
+struct InputLatch {
+    just_pressed: HashSet<GameAction>,
+}
+
+pub fn latch_inputs(
+    mut latch: ResMut<InputLatch>,
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+) {
+    for action in GameAction::ALL {
+        if keyboard_input.just_pressed(settings.key_bindings.key(action)) {
+            latch.just_pressed.insert(action);
+        }
+    }
+}
+
+pub fn begin_tick(
+    mut tick_input: ResMut<TickInput>,
+    mut latch: ResMut<InputLatch>,
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+) {
+    tick_input.pressed = GameAction::ALL.iter().copied().filter(|action| keyboard_input.pressed(settings.key_bindings.key(*action))).collect();
+    tick_input.just_pressed = std::mem::take(&mut latch.just_pressed);
+}
+
+// Simulated position of the last two ticks, the sprite is drawn in between
+#[derive(Component)]
+pub struct SimPosition {
+    previous: Vec2,
+    current: Vec2,
+}
+
+impl SimPosition {
+    pub fn new(x: f32, y: f32) -> Self {
+        SimPosition { previous: Vec2::new(x, y), current: Vec2::new(x, y) }
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn snap(&mut self, x: f32, y: f32) {
+        *self = SimPosition::new(x, y);
+    }
+}
+
+pub fn commit_positions<T: EntityBehavior + Component>(
+    mut query: Query<(&mut SimPosition, &T)>,
+) {
+    for (mut position, entity) in query.iter_mut() {
+        position.previous = position.current;
+        position.current = Vec2::new(entity.x(), entity.y());
+    }
+}
+
+pub fn interpolate_positions(
+    mut query: Query<(&mut Transform, &SimPosition)>,
+    fixed_time: Res<FixedTime>,
+) {
+    let alpha = (fixed_time.accumulated().as_secs_f32() / fixed_time.period.as_secs_f32()).clamp(0., 1.);
+    for (mut transform, position) in query.iter_mut() {
+        let interpolated = position.previous.lerp(position.current, alpha);
+        transform.translation.x = interpolated.x;
+        transform.translation.y = interpolated.y;
+    }
+}
@@ -0,0 +1,308 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::chunks::{ChunkCoord, ChunkMember};
+use crate::collisions::CollisionComponent;
+use crate::constants::*;
+use crate::worldgen::Biome;
+
+pub const DEFAULT_BACKGROUND: &str = "Background/background.png";
+
+pub struct TileMapPlugin;
+
+impl Plugin for TileMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TileMap>()
+            .init_resource::<TileAssets>();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TileKind {
+    Grass,
+    Path,
+    Water,
+    Cliff,
+}
+
+impl TileKind {
+    pub fn is_solid(&self) -> bool {
+        matches!(self, TileKind::Water | TileKind::Cliff)
+    }
+
+    // Grass is the background image, only the other tiles are drawn
+    fn atlas_index(&self) -> Option<usize> {
+        match self {
+            TileKind::Grass => None,
+            TileKind::Path => Some(0),
+            TileKind::Water => Some(1),
+            TileKind::Cliff => Some(2),
+        }
+    }
+}
+
+// Every entity of the tile layer, sprites and merged collisions
+#[derive(Component)]
+pub struct TileLayer;
+
+// Tile (0, 0) is the bottom left corner of the map
+#[derive(Resource, Clone)]
+pub struct TileMap {
+    width: usize,
+    height: usize,
+    tiles: Vec<TileKind>,
+    biomes: Vec<Biome>,
+}
+
+impl Default for TileMap {
+    fn default() -> Self {
+        TileMap::new(MAP_TILES, MAP_TILES)
+    }
+}
+
+impl TileMap {
+    pub fn new(width: usize, height: usize) -> Self {
+        TileMap { width, height, tiles: vec![TileKind::Grass; width * height], biomes: vec![Biome::Clearing; width * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> TileKind {
+        self.tiles[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, kind: TileKind) {
+        if x < self.width && y < self.height {
+            self.tiles[y * self.width + x] = kind;
+        }
+    }
+
+    pub fn biome(&self, x: usize, y: usize) -> Biome {
+        self.biomes[y * self.width + x]
+    }
+
+    pub fn set_biome(&mut self, x: usize, y: usize, biome: Biome) {
+        if x < self.width && y < self.height {
+            self.biomes[y * self.width + x] = biome;
+        }
+    }
+
+    pub fn tile_at(&self, x: f32, y: f32) -> Option<(usize, usize)> {
+        let tile_x = ((x + MAP_SIZE / 2.) / TILE_SIZE).floor();
+        let tile_y = ((y + MAP_SIZE / 2.) / TILE_SIZE).floor();
+        if tile_x < 0. || tile_y < 0. {
+            return None;
+        }
+        let (tile_x, tile_y) = (tile_x as usize, tile_y as usize);
+        (tile_x < self.width && tile_y < self.height).then_some((tile_x, tile_y))
+    }
+
+    pub fn tile_center(&self, x: usize, y: usize) -> (f32, f32) {
+        (x as f32 * TILE_SIZE - MAP_SIZE / 2. + TILE_SIZE / 2.,
+         y as f32 * TILE_SIZE - MAP_SIZE / 2. + TILE_SIZE / 2.)
+    }
+
+    // Whether every tile under a hitbox centered on (x, y) matches, outside of the map never does
+    fn is_area(&self, x: f32, y: f32, w: f32, h: f32, matches: impl Fn(TileKind) -> bool) -> bool {
+        let (Some((min_x, min_y)), Some((max_x, max_y))) = (self.tile_at(x - w / 2., y - h / 2.), self.tile_at(x + w / 2., y + h / 2.)) else {
+            return false;
+        };
+        (min_y..=max_y).all(|tile_y| (min_x..=max_x).all(|tile_x| matches(self.get(tile_x, tile_y))))
+    }
+
+    pub fn is_area_free(&self, x: f32, y: f32, w: f32, h: f32) -> bool {
+        self.is_area(x, y, w, h, |kind| !kind.is_solid())
+    }
+
+    // Props stay off the paths so that they never block them
+    pub fn is_area_grass(&self, x: f32, y: f32, w: f32, h: f32) -> bool {
+        self.is_area(x, y, w, h, |kind| kind == TileKind::Grass)
+    }
+
+    // Solid tiles of an area merged into few rectangles (x, y, w, h in tiles): runs of each row, then the same run on the following rows
+    pub fn collision_rects(&self, first_x: usize, first_y: usize, width: usize, height: usize) -> Vec<(usize, usize, usize, usize)> {
+        let (end_x, end_y) = ((first_x + width).min(self.width), (first_y + height).min(self.height));
+        let mut rects: Vec<(usize, usize, usize, usize)> = Vec::new();
+        let mut open: Vec<usize> = Vec::new(); // rects that reached the previous row
+        for y in first_y..end_y {
+            let mut runs = Vec::new();
+            let mut x = first_x;
+            while x < end_x {
+                if !self.get(x, y).is_solid() {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < end_x && self.get(x, y).is_solid() {
+                    x += 1;
+                }
+                runs.push((start, x - start));
+            }
+
+            let mut still_open = Vec::new();
+            for (start, length) in runs {
+                let extended = open.iter().copied().find(|&i| rects[i].0 == start && rects[i].2 == length);
+                match extended {
+                    Some(i) => {
+                        rects[i].3 += 1;
+                        still_open.push(i);
+                    },
+                    None => {
+                        rects.push((start, y, length, 1));
+                        still_open.push(rects.len() - 1);
+                    },
+                }
+            }
+            open = still_open;
+        }
+        rects
+    }
+
+    pub fn fill(&mut self, x: i32, y: i32, radius: i32, kind: TileKind) {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if x + dx >= 0 && y + dy >= 0 {
+                    self.set((x + dx) as usize, (y + dy) as usize, kind);
+                }
+            }
+        }
+    }
+
+    // Terrain only: a river running from the bottom to the top of one half of the map, a road crossing it and a few cliffs
+    pub fn generate(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed.wrapping_add(OFFSET_TILEMAP));
+        let mut map = TileMap::default();
+        let (width, height) = (map.width as i32, map.height as i32);
+        let center = width / 2;
+
+        for _ in 0..CLIFF_RIDGES {
+            let length = rng.gen_range(CLIFF_RIDGE_MIN_LENGTH..=CLIFF_RIDGE_MAX_LENGTH);
+            let (dx, dy) = if rng.gen_bool(0.5) { (1, 0) } else { (0, 1) };
+            let x = rng.gen_range(2..width - 2 - length * dx);
+            let y = rng.gen_range(2..height - 2 - length * dy);
+            for i in 0..length {
+                map.set((x + i * dx) as usize, (y + i * dy) as usize, TileKind::Cliff);
+            }
+        }
+
+        let (min_x, max_x) = if rng.gen_bool(0.5) { (3, center - SPAWN_CLEAR_RADIUS - RIVER_WIDTH) } else { (center + SPAWN_CLEAR_RADIUS + RIVER_WIDTH, width - 4) };
+        let mut river_x = rng.gen_range(min_x..=max_x);
+        for y in 0..height {
+            river_x = (river_x + rng.gen_range(-1..=1)).clamp(min_x, max_x);
+            map.fill(river_x, y, RIVER_WIDTH / 2, TileKind::Water);
+        }
+
+        let mut road_y = center;
+        for x in 0..width {
+            road_y = (road_y + rng.gen_range(-1..=1)).clamp(center - ROAD_WANDER, center + ROAD_WANDER);
+            map.fill(x, road_y, ROAD_WIDTH / 2, TileKind::Path);
+        }
+
+        map.fill(center, center, SPAWN_CLEAR_RADIUS, TileKind::Grass);
+
+        for x in 0..map.width {
+            map.set(x, 0, TileKind::Cliff);
+            map.set(x, map.height - 1, TileKind::Cliff);
+        }
+        for y in 0..map.height {
+            map.set(0, y, TileKind::Cliff);
+            map.set(map.width - 1, y, TileKind::Cliff);
+        }
+        map
+    }
+}
+
+#[derive(Resource)]
+pub struct TileAssets {
+    atlas: Handle<TextureAtlas>,
+    background: Handle<Image>,
+}
+
+impl FromWorld for TileAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        let texture_handle = asset_server.load("Background/tiles.png");
+        let background = asset_server.load(DEFAULT_BACKGROUND);
+
+        let mut texture_atlases = world.resource_mut::<Assets<TextureAtlas>>();
+        let atlas = texture_atlases.add(TextureAtlas::from_grid(texture_handle, Vec2::new(TILE_SIZE, TILE_SIZE), 3, 1, None, None));
+        TileAssets { atlas, background }
+    }
+}
+
+impl TileAssets {
+    // Maps can have their own background, tiled the same way as the default one
+    pub fn set_background(&mut self, asset_server: &AssetServer, path: Option<&str>) {
+        self.background = asset_server.load(path.unwrap_or(DEFAULT_BACKGROUND).to_string());
+    }
+}
+
+// Piece of the background for a chunk index, and whether it is mirrored: every other repeat is mirrored so that the image tiles without seams
+fn background_piece(index: usize, pieces: usize) -> (usize, bool) {
+    let piece = index % pieces;
+    if (index / pieces) % 2 == 1 {
+        (pieces - 1 - piece, true)
+    } else {
+        (piece, false)
+    }
+}
+
+pub fn spawn_chunk_tiles(
+    commands: &mut Commands,
+    tile_map: &TileMap,
+    assets: &TileAssets,
+    chunk: ChunkCoord,
+) {
+    let (first_x, first_y) = chunk.first_tile();
+    let (center_x, center_y) = chunk.center();
+    // Each chunk shows a piece of the background image
+    let pieces = (BACKGROUND_IMAGE_SIZE / CHUNK_SIZE) as usize;
+    let (column, flip_x) = background_piece(chunk.x, pieces);
+    let (row, flip_y) = background_piece(chunk.y, pieces);
+    let piece_x = column as f32 * CHUNK_SIZE;
+    // The image goes down, the world goes up
+    let piece_y = (pieces - 1 - row) as f32 * CHUNK_SIZE;
+
+    commands.spawn((SpatialBundle::default(), TileLayer, ChunkMember(chunk)))
+        .with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                texture: assets.background.clone(),
+                sprite: Sprite {
+                    rect: Some(Rect::new(piece_x, piece_y, piece_x + CHUNK_SIZE, piece_y + CHUNK_SIZE)),
+                    flip_x,
+                    flip_y,
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(center_x, center_y, Z_LAYER_BACKGROUND),
+                ..Default::default()
+            });
+            for y in first_y..first_y + CHUNK_TILES {
+                for x in first_x..first_x + CHUNK_TILES {
+                    let Some(index) = tile_map.get(x, y).atlas_index() else {
+                        continue;
+                    };
+                    let (tile_x, tile_y) = tile_map.tile_center(x, y);
+                    parent.spawn(SpriteSheetBundle {
+                        texture_atlas: assets.atlas.clone(),
+                        transform: Transform::from_xyz(tile_x, tile_y, Z_LAYER_TILES),
+                        sprite: TextureAtlasSprite::new(index),
+                        ..Default::default()
+                    });
+                }
+            }
+        });
+
+    for (x, y, w, h) in tile_map.collision_rects(first_x, first_y, CHUNK_TILES, CHUNK_TILES) {
+        let (left, bottom) = tile_map.tile_center(x, y);
+        let (w, h) = (w as f32 * TILE_SIZE, h as f32 * TILE_SIZE);
+        let center_x = left - TILE_SIZE / 2. + w / 2.;
+        let center_y = bottom - TILE_SIZE / 2. + h / 2.;
+        commands.spawn((CollisionComponent::new(center_x, center_y, w, h), TileLayer, ChunkMember(chunk)));
+    }
+}
@@ -0,0 +1,495 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::GameConfig;
+use crate::GameState;
+use crate::chunks::ChunkCoord;
+use crate::collisions::{CollisionComponent, Collisionable};
+use crate::constants::*;
+use crate::structures::{Sanctuary, Tower};
+use crate::tilemap::{TileAssets, TileKind, TileMap};
+
+pub struct WorldGenPlugin;
+
+impl Plugin for WorldGenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldPlan>()
+            .init_resource::<CustomWorld>()
+            .add_systems(OnTransition { from: GameState::Loading, to: GameState::Playing }, generate_world);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Biome {
+    Clearing,
+    Forest,
+    Graveyard,
+}
+
+impl Biome {
+    fn from_noise(value: f32) -> Self {
+        if value < BIOME_CLEARING_THRESHOLD {
+            Biome::Clearing
+        } else if value < BIOME_GRAVEYARD_THRESHOLD {
+            Biome::Forest
+        } else {
+            Biome::Graveyard
+        }
+    }
+
+    // Chance for a candidate prop to be kept in this biome
+    fn prop_density(&self, kind: PropKind) -> f64 {
+        match (self, kind) {
+            (Biome::Forest, PropKind::Tree) => 1.,
+            (Biome::Forest, PropKind::Bush) => 0.5,
+            (Biome::Forest, PropKind::Grave) => 0.05,
+            (Biome::Graveyard, PropKind::Tree) => 0.1,
+            (Biome::Graveyard, PropKind::Bush) => 0.2,
+            (Biome::Graveyard, PropKind::Grave) => 1.,
+            (Biome::Clearing, PropKind::Tree) => 0.05,
+            (Biome::Clearing, PropKind::Bush) => 0.6,
+            (Biome::Clearing, PropKind::Grave) => 0.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum PropKind {
+    Tree,
+    Bush,
+    Grave,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlannedProp {
+    pub kind: PropKind,
+    pub x: f32,
+    pub y: f32,
+    pub index: usize,
+    // Hitbox set by hand, offset from the prop and size
+    pub collision: Option<(f32, f32, f32, f32)>,
+}
+
+impl PlannedProp {
+    // Bushes can be walked through
+    pub fn hitbox(&self) -> Option<CollisionComponent> {
+        if let Some((offset_x, offset_y, w, h)) = self.collision {
+            return Some(CollisionComponent::new(self.x + offset_x, self.y + offset_y, w, h));
+        }
+        match self.kind {
+            PropKind::Tree => Some(CollisionComponent::new(self.x, self.y - TREE_HEIGHT / 2. + 12., 5., 5.)),
+            PropKind::Bush => None,
+            PropKind::Grave => {
+                let (offset, w, h) = match self.index {
+                    0 => (0., 32., 57.),
+                    1 => (9., 30., 17.),
+                    2 => (10., 56., 22.),
+                    _ => (16., 32., 40.),
+                };
+                Some(CollisionComponent::new(self.x, self.y - offset, w, h))
+            },
+        }
+    }
+
+    // Chunk the prop is loaded with, the one of its footprint
+    pub fn chunk(&self) -> Option<ChunkCoord> {
+        let (x, y, _, _) = self.footprint();
+        ChunkCoord::at(x, y)
+    }
+
+    // Area that has to be grass, the hitbox or the whole sprite for bushes
+    pub fn footprint(&self) -> (f32, f32, f32, f32) {
+        match self.hitbox() {
+            Some(hitbox) => hitbox.get_hitbox(),
+            None => (self.x, self.y, BUSH_WIDTH, BUSH_HEIGHT),
+        }
+    }
+}
+
+// Where everything of a run goes, computed before anything is spawned
+#[derive(Resource, Clone, Default)]
+pub struct WorldPlan {
+    pub tower: (f32, f32),
+    pub sanctuaries: Vec<(f32, f32)>,
+    pub props: Vec<PlannedProp>,
+    // Enemies placed by hand (position and whether they guard a shrine), each chunk draws its own when there are none
+    pub ennemies: Option<Vec<(f32, f32, bool)>>,
+    // Image under the tiles, the default one when there is none
+    pub background: Option<String>,
+}
+
+// Plan made in the editor, played instead of the one of the seed
+#[derive(Resource, Default)]
+pub struct CustomWorld(pub Option<WorldPlan>);
+
+fn lattice(seed: u64, x: i32, y: i32) -> f32 {
+    let mut hash = seed ^ (x as u64).wrapping_mul(0x9E3779B97F4A7C15) ^ (y as u64).wrapping_mul(0xC2B2AE3D27D4EB4F);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xFF51AFD7ED558CCD);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xC4CEB9FE1A85EC53);
+    hash ^= hash >> 33;
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
+
+fn value_noise(seed: u64, x: f32, y: f32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let smooth = |t: f32| t * t * (3. - 2. * t);
+    let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+    let (ix, iy) = (x0 as i32, y0 as i32);
+    let bottom = lattice(seed, ix, iy) * (1. - tx) + lattice(seed, ix + 1, iy) * tx;
+    let top = lattice(seed, ix, iy + 1) * (1. - tx) + lattice(seed, ix + 1, iy + 1) * tx;
+    bottom * (1. - ty) + top * ty
+}
+
+// Two octaves, between 0 and 1
+pub fn biome_noise(seed: u64, tile_x: usize, tile_y: usize) -> f32 {
+    let (x, y) = (tile_x as f32 * BIOME_NOISE_SCALE, tile_y as f32 * BIOME_NOISE_SCALE);
+    (value_noise(seed, x, y) * 2. + value_noise(seed.wrapping_add(1), x * 2., y * 2.)) / 3.
+}
+
+fn paint_biomes(tile_map: &mut TileMap, seed: u64) {
+    for y in 0..tile_map.height() {
+        for x in 0..tile_map.width() {
+            tile_map.set_biome(x, y, Biome::from_noise(biome_noise(seed.wrapping_add(OFFSET_BIOME), x, y)));
+        }
+    }
+}
+
+fn spawn_area() -> CollisionComponent {
+    let size = (SPAWN_CLEAR_RADIUS * 2 + 1) as f32 * TILE_SIZE;
+    CollisionComponent::new(0., 0., size, size)
+}
+
+fn plan_tower(tile_map: &TileMap, seed: u64) -> (f32, f32) {
+    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(OFFSET_TOWER));
+    loop {
+        let x = rng.gen_range(-MAP_SIZE / 2. + TOWER_WIDTH..MAP_SIZE / 2. - TOWER_WIDTH);
+        let y = rng.gen_range(-MAP_SIZE / 2. + TOWER_HEIGHT + PLAYER_HITBOX_HEIGHT..MAP_SIZE / 2. - TOWER_HEIGHT);
+        if tile_map.is_area_free(x, y, TOWER_WIDTH, TOWER_HEIGHT) && !Tower::new(x, y).would_collide_with(&spawn_area()) {
+            return (x, y);
+        }
+    }
+}
+
+fn is_sanctuary_free(sanctuary: &Sanctuary, tile_map: &TileMap, tower: &Tower, added: &[Sanctuary]) -> bool {
+    let (x, y) = sanctuary.get_pos();
+    tile_map.is_area_free(x, y, SANCTUARY_WIDTH, SANCTUARY_HEIGHT)
+        && !sanctuary.would_collide_with(tower)
+        && !sanctuary.would_collide_with(&spawn_area())
+        && !added.iter().any(|other| sanctuary.would_collide_with(other))
+}
+
+// Always the requested number of shrines: random tries first, then every tile of the map from a random one
+fn plan_sanctuaries(tile_map: &TileMap, tower: &Tower, seed: u64) -> Vec<(f32, f32)> {
+    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(OFFSET_SANCTUARY));
+    let tiles = tile_map.width() * tile_map.height();
+    let mut added: Vec<Sanctuary> = Vec::new();
+    for _ in 0..SANCTUARY_NB {
+        let mut found = (0..SANCTUARY_MAX_ATTEMPTS)
+            .map(|_| Sanctuary::new_random_position(&mut rng))
+            .find(|sanctuary| is_sanctuary_free(sanctuary, tile_map, tower, &added));
+        if found.is_none() {
+            let first = rng.gen_range(0..tiles);
+            found = (0..tiles)
+                .map(|i| (first + i) % tiles)
+                .map(|i| tile_map.tile_center(i % tile_map.width(), i / tile_map.width()))
+                .map(|(x, y)| Sanctuary::new(x, y))
+                .find(|sanctuary| is_sanctuary_free(sanctuary, tile_map, tower, &added));
+        }
+        match found {
+            Some(sanctuary) => added.push(sanctuary),
+            None => error!(target: "zelda::world", "no room left for shrine {}", added.len() + 1),
+        }
+    }
+    added.iter().map(|sanctuary| sanctuary.get_pos()).collect()
+}
+
+// Walks from one tile to the other, mostly along the longest axis, and lays a path wide enough to walk on
+fn carve_path(tile_map: &mut TileMap, from: (usize, usize), to: (usize, usize), rng: &mut StdRng) {
+    let (mut x, mut y) = (from.0 as i32, from.1 as i32);
+    let (to_x, to_y) = (to.0 as i32, to.1 as i32);
+    let radius = PATH_WIDTH / 2;
+    let (min_x, max_x) = (1 + radius, tile_map.width() as i32 - 2 - radius);
+    let (min_y, max_y) = (1 + radius, tile_map.height() as i32 - 2 - radius);
+    loop {
+        tile_map.fill(x.clamp(min_x, max_x), y.clamp(min_y, max_y), radius, TileKind::Path);
+        let (dx, dy) = (to_x - x, to_y - y);
+        if dx == 0 && dy == 0 {
+            break;
+        }
+        let along_x = if dx == 0 {
+            false
+        } else if dy == 0 {
+            true
+        } else {
+            (dx.abs() >= dy.abs()) != rng.gen_bool(PATH_WANDER)
+        };
+        if along_x {
+            x += dx.signum();
+        } else {
+            y += dy.signum();
+        }
+    }
+}
+
+// Spawn to tower, then each shrine to the closest place already linked
+fn carve_paths(tile_map: &mut TileMap, plan: &WorldPlan, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(OFFSET_PATHS));
+    let tile = |tile_map: &TileMap, (x, y): (f32, f32)| tile_map.tile_at(x, y).unwrap_or((tile_map.width() / 2, tile_map.height() / 2));
+
+    let mut linked = vec![tile(tile_map, (0., 0.)), tile(tile_map, plan.tower)];
+    carve_path(tile_map, linked[0], linked[1], &mut rng);
+    for &sanctuary in &plan.sanctuaries {
+        let target = tile(tile_map, sanctuary);
+        let distance = |(x, y): (usize, usize)| x.abs_diff(target.0) + y.abs_diff(target.1);
+        let from = linked.iter().copied().min_by_key(|&point| distance(point)).unwrap();
+        carve_path(tile_map, from, target, &mut rng);
+        linked.push(target);
+    }
+}
+
+fn plan_props(tile_map: &TileMap, plan: &WorldPlan, seed: u64) -> Vec<PlannedProp> {
+    let mut blocked = vec![spawn_area()];
+    blocked.extend(structure_hitboxes(plan));
+    ChunkCoord::all().flat_map(|chunk| plan_chunk_props(tile_map, &blocked, seed, chunk)).collect()
+}
+
+// A chunk gets the same props whatever the others get, each of them stays inside its chunk
+fn plan_chunk_props(tile_map: &TileMap, structures: &[CollisionComponent], seed: u64, chunk: ChunkCoord) -> Vec<PlannedProp> {
+    let (center_x, center_y) = chunk.center();
+    let half = CHUNK_SIZE / 2.;
+    let mut blocked = structures.to_vec();
+    let mut props = Vec::new();
+    for (kind, candidates, offset) in [(PropKind::Tree, TREE_CANDIDATES_PER_CHUNK, OFFSET_TREE),
+                                       (PropKind::Grave, GRAVE_CANDIDATES_PER_CHUNK, OFFSET_GRAVE),
+                                       (PropKind::Bush, BUSH_CANDIDATES_PER_CHUNK, OFFSET_BUSH)] {
+        let mut rng = chunk.rng(seed.wrapping_add(offset));
+        for _ in 0..candidates {
+            let x = rng.gen_range(center_x - half..center_x + half);
+            let y = rng.gen_range(center_y - half..center_y + half);
+            let prop = PlannedProp { kind, x, y, index: rng.gen_range(0..PROP_VARIANTS), collision: None };
+            let roll: f64 = rng.gen();
+
+            let Some(biome) = tile_map.tile_at(x, y).map(|(tile_x, tile_y)| tile_map.biome(tile_x, tile_y)) else {
+                continue;
+            };
+            let (fx, fy, fw, fh) = prop.footprint();
+            let footprint = CollisionComponent::new(fx, fy, fw, fh);
+            if roll >= biome.prop_density(kind)
+                || !chunk.contains_area(fx, fy, fw, fh)
+                || !tile_map.is_area_grass(fx, fy, fw, fh)
+                || blocked.iter().any(|other| footprint.would_collide_with(other)) {
+                continue;
+            }
+            if let Some(hitbox) = prop.hitbox() {
+                blocked.push(hitbox);
+            }
+            props.push(prop);
+        }
+    }
+    props
+}
+
+fn spawn_tile(tile_map: &TileMap) -> (usize, usize) {
+    tile_map.tile_at(0., 0.).unwrap_or((tile_map.width() / 2, tile_map.height() / 2))
+}
+
+// Tower first, then the shrines
+fn structure_hitboxes(plan: &WorldPlan) -> Vec<CollisionComponent> {
+    std::iter::once(Tower::new(plan.tower.0, plan.tower.1).get_collision_component())
+        .chain(plan.sanctuaries.iter().map(|&(x, y)| Sanctuary::new(x, y).get_collision_component()))
+        .collect()
+}
+
+// Tiles under a hitbox grown by the margin, clamped to the map
+fn covered_tiles(tile_map: &TileMap, hitbox: &CollisionComponent, margin: f32) -> Vec<(usize, usize)> {
+    let (x, y, w, h) = hitbox.get_hitbox();
+    let clamp = |value: f32| value.clamp(-MAP_SIZE / 2., MAP_SIZE / 2. - 1.);
+    let (Some(min), Some(max)) = (tile_map.tile_at(clamp(x - w / 2. - margin), clamp(y - h / 2. - margin)),
+                                  tile_map.tile_at(clamp(x + w / 2. + margin), clamp(y + h / 2. + margin))) else {
+        return Vec::new();
+    };
+    (min.1..=max.1).flat_map(|tile_y| (min.0..=max.0).map(move |tile_x| (tile_x, tile_y))).collect()
+}
+
+// Tiles that cannot be walked on because of the terrain, a structure or a prop
+struct CollisionGrid {
+    width: usize,
+    height: usize,
+    blocked: Vec<bool>,
+}
+
+impl CollisionGrid {
+    fn new(tile_map: &TileMap, plan: &WorldPlan) -> Self {
+        let (width, height) = (tile_map.width(), tile_map.height());
+        let mut grid = CollisionGrid {
+            width,
+            height,
+            blocked: (0..width * height).map(|i| tile_map.get(i % width, i / width).is_solid()).collect(),
+        };
+        let hitboxes = structure_hitboxes(plan).into_iter().chain(plan.props.iter().filter_map(PlannedProp::hitbox));
+        for hitbox in hitboxes {
+            for (x, y) in covered_tiles(tile_map, &hitbox, 0.) {
+                grid.blocked[y * width + x] = true;
+            }
+        }
+        grid
+    }
+
+    fn flood_fill(&self, start: (usize, usize)) -> Vec<bool> {
+        let mut reached = vec![false; self.width * self.height];
+        let mut queue = VecDeque::from([start]);
+        reached[start.1 * self.width + start.0] = true;
+        while let Some((x, y)) = queue.pop_front() {
+            for (next_x, next_y) in neighbours(x, y) {
+                if next_x >= self.width || next_y >= self.height {
+                    continue;
+                }
+                let index = next_y * self.width + next_x;
+                if !self.blocked[index] && !reached[index] {
+                    reached[index] = true;
+                    queue.push_back((next_x, next_y));
+                }
+            }
+        }
+        reached
+    }
+}
+
+// Out of the map neighbours wrap around to huge values, callers check the bounds
+fn neighbours(x: usize, y: usize) -> [(usize, usize); 4] {
+    [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)]
+}
+
+// Index of the structures (0 is the tower) that no tile around can be reached from the spawn
+pub fn unreachable_structures(tile_map: &TileMap, plan: &WorldPlan) -> Vec<usize> {
+    let width = tile_map.width();
+    let reached = CollisionGrid::new(tile_map, plan).flood_fill(spawn_tile(tile_map));
+    structure_hitboxes(plan).iter().enumerate()
+        .filter(|(_, hitbox)| !covered_tiles(tile_map, hitbox, TILE_SIZE).iter().any(|&(x, y)| reached[y * width + x]))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+// Shortest way from the spawn to the tiles around a structure going through anything but the other structures, laid as a path
+fn carve_to_structure(tile_map: &mut TileMap, plan: &WorldPlan, target: usize) {
+    let (width, height) = (tile_map.width(), tile_map.height());
+    let radius = PATH_WIDTH / 2;
+    let hitboxes = structure_hitboxes(plan);
+
+    let mut avoided = vec![false; width * height];
+    for (index, hitbox) in hitboxes.iter().enumerate() {
+        if index != target {
+            for (x, y) in covered_tiles(tile_map, hitbox, radius as f32 * TILE_SIZE) {
+                avoided[y * width + x] = true;
+            }
+        }
+    }
+    let inside = covered_tiles(tile_map, &hitboxes[target], 0.);
+    let goal: Vec<(usize, usize)> = covered_tiles(tile_map, &hitboxes[target], TILE_SIZE).into_iter().filter(|tile| !inside.contains(tile)).collect();
+
+    let margin = 1 + radius as usize;
+    let start = spawn_tile(tile_map);
+    let mut previous: Vec<Option<(usize, usize)>> = vec![None; width * height];
+    let mut queue = VecDeque::from([start]);
+    previous[start.1 * width + start.0] = Some(start);
+    while let Some(tile) = queue.pop_front() {
+        if goal.contains(&tile) {
+            let mut current = tile;
+            while current != start {
+                tile_map.fill(current.0 as i32, current.1 as i32, radius, TileKind::Path);
+                current = previous[current.1 * width + current.0].unwrap();
+            }
+            return;
+        }
+        for (next_x, next_y) in neighbours(tile.0, tile.1) {
+            if next_x < margin || next_y < margin || next_x >= width - margin || next_y >= height - margin {
+                continue;
+            }
+            let index = next_y * width + next_x;
+            if !avoided[index] && previous[index].is_none() {
+                previous[index] = Some(tile);
+                queue.push_back((next_x, next_y));
+            }
+        }
+    }
+}
+
+fn plan_terrain(seed: u64) -> TileMap {
+    let mut tile_map = TileMap::generate(seed);
+    paint_biomes(&mut tile_map, seed);
+    tile_map
+}
+
+// Terrain, biomes, structures, the paths linking them and the props, only from the seed
+pub fn plan_world(seed: u64) -> (TileMap, WorldPlan) {
+    let mut tile_map = plan_terrain(seed);
+
+    let tower = plan_tower(&tile_map, seed);
+    let sanctuaries = plan_sanctuaries(&tile_map, &Tower::new(tower.0, tower.1), seed);
+    let mut plan = WorldPlan { tower, sanctuaries, props: Vec::new(), ennemies: None, background: None };
+
+    carve_paths(&mut tile_map, &plan, seed);
+    plan.props = plan_props(&tile_map, &plan, seed);
+
+    // The paths can still go under another structure, those left behind get a path of their own and the props on it are removed
+    for target in unreachable_structures(&tile_map, &plan) {
+        carve_to_structure(&mut tile_map, &plan, target);
+    }
+    plan.props.retain(|prop| {
+        let (x, y, w, h) = prop.footprint();
+        tile_map.is_area_grass(x, y, w, h)
+    });
+    (tile_map, plan)
+}
+
+// Terrain under a plan made by hand: the paths go to its structures, its props stay where they were put
+pub fn terrain_for_plan(seed: u64, plan: &WorldPlan) -> TileMap {
+    let mut tile_map = plan_terrain(seed);
+    carve_paths(&mut tile_map, plan, seed);
+    for target in unreachable_structures(&tile_map, plan) {
+        carve_to_structure(&mut tile_map, plan, target);
+    }
+    tile_map
+}
+
+pub fn generate_world(
+    mut tile_map: ResMut<TileMap>,
+    mut plan: ResMut<WorldPlan>,
+    game_config: Res<GameConfig>,
+    custom_world: Res<CustomWorld>,
+    mut tile_assets: ResMut<TileAssets>,
+    asset_server: Res<AssetServer>,
+) {
+    let (new_tile_map, new_plan) = match &custom_world.0 {
+        Some(custom_plan) => (terrain_for_plan(game_config.seed, custom_plan), custom_plan.clone()),
+        None => plan_world(game_config.seed),
+    };
+    *tile_map = new_tile_map;
+    *plan = new_plan;
+    tile_assets.set_background(&asset_server, plan.background.as_deref());
+    let hitboxes = structure_hitboxes(&plan);
+    for index in unreachable_structures(&tile_map, &plan) {
+        let (x, y) = hitboxes[index].get_pos();
+        warn!(target: "zelda::world", "structure at ({:.0}, {:.0}) cannot be reached from the spawn", x, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The promise of plan_sanctuaries and carve_to_structure, seeds near the top of the range included
+    #[test]
+    fn every_shrine_is_planned_and_reachable() {
+        for seed in (0..100).chain(u64::MAX - 10..=u64::MAX) {
+            let (tile_map, plan) = plan_world(seed);
+            assert_eq!(plan.sanctuaries.len(), SANCTUARY_NB as usize, "seed {}", seed);
+            assert!(unreachable_structures(&tile_map, &plan).is_empty(), "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+
+use crate::constants::*;
+
+pub struct YSortPlugin;
+
+impl Plugin for YSortPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, y_sort.before(TransformSystem::TransformPropagate));
+    }
+}
+
+// World sprite drawn in front of what stands higher on the map, from where it touches the ground
+#[derive(Component)]
+pub struct YSort {
+    // From the center of the sprite down to its feet
+    foot_offset: f32,
+}
+
+impl YSort {
+    pub fn new(foot_offset: f32) -> Self {
+        YSort { foot_offset }
+    }
+
+    pub fn foot_offset(&self) -> f32 {
+        self.foot_offset
+    }
+}
+
+// z of a foot at this height, between the tiles and the camera whatever the map size
+pub fn y_depth(foot_y: f32) -> f32 {
+    Z_LAYER_WORLD + (MAP_SIZE / 2. - foot_y).clamp(0., MAP_SIZE) / MAP_SIZE * Z_WORLD_DEPTH
+}
+
+fn y_sort(
+    mut query: Query<(&mut Transform, &YSort), Or<(Changed<Transform>, Added<YSort>)>>,
+) {
+    for (mut transform, ysort) in query.iter_mut() {
+        let z = y_depth(transform.translation.y - ysort.foot_offset);
+        // Otherwise Changed would fire again every frame
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
+}